@@ -0,0 +1,323 @@
+//! Online backups of RocksDB databases.
+//!
+//! A backup directory holds any number of backups of a single database, each identified by an
+//! increasing id:
+//!
+//! ```ignore
+//! shared/000012_<checksum>_<size>.sst   table files, shared by every backup which includes them
+//! private/3/MANIFEST-000005            the remaining files of backup 3: CURRENT, MANIFEST and logs
+//! meta/3                               the files of backup 3, with their sizes and checksums
+//! ```
+//!
+//! Table files are immutable, so each backup only copies (or hard links) the table files which
+//! are not already held by an earlier backup. Shared table files are named by their checksum and
+//! size as well as their file name, since a database which is recreated in the same directory
+//! reuses the names of table files. Backups are taken while the database is running;
+//! file deletions are disabled for the duration of the backup so that the copied files remain
+//! consistent with each other.
+//!
+//! A backup engine must not be used by more than one task at a time.
+//!
+//! For a one-off copy of a database, `Database::checkpoint` creates an openable copy of the
+//! database in a new directory, hard linking table files where possible.
+
+use std::collections::HashSet;
+use std::io;
+use std::io::fs;
+use std::num::from_str_radix;
+
+use super::{Database, FlushOptions};
+use super::files::{FNV_OFFSET, copy_file, copy_manifest, file_checksum, list_private_files,
+                   list_table_files, rocksdb_error, update_checksum};
+
+/// A summary of a backup.
+#[deriving(Clone, PartialEq, Show)]
+pub struct BackupInfo {
+    /// The id of the backup.
+    pub id: u32,
+    /// The total size of the files in the backup, in bytes, including shared table files.
+    pub size: u64,
+    /// The number of files in the backup.
+    pub num_files: uint
+}
+
+/// A file held by a backup.
+struct BackupFile {
+    /// The path of the file, relative to the backup directory.
+    path: String,
+    size: u64,
+    checksum: u64
+}
+
+/// Creates, verifies and restores backups in a backup directory.
+pub struct BackupEngine {
+    dir: Path,
+    hard_link: bool
+}
+
+impl BackupEngine {
+
+    /// Open a backup directory, creating it if necessary.
+    pub fn open(dir: &Path) -> io::IoResult<BackupEngine> {
+        for subdir in ["shared", "private", "meta"].iter() {
+            try!(fs::mkdir_recursive(&dir.join(*subdir), io::USER_RWX));
+        }
+        Ok(BackupEngine { dir: dir.clone(), hard_link: true })
+    }
+
+    /// If true, table files are hard linked into the backup directory instead of copied when the
+    /// backup directory is on the same file system as the database. Table files are never
+    /// modified, so hard links are safe and save both time and space.
+    ///
+    /// Default: true
+    pub fn set_hard_link(&mut self, hard_link: bool) -> &mut BackupEngine {
+        self.hard_link = hard_link;
+        self
+    }
+
+    /// Create a new backup of the database, returning its id. If `flush_before_backup` is true,
+    /// the memtables are flushed first, so that the backup holds fewer write ahead log files.
+    pub fn create_backup(&self, db: &Database, flush_before_backup: bool) -> io::IoResult<u32> {
+        if flush_before_backup {
+            try!(db.flush(&FlushOptions::new()).map_err(rocksdb_error));
+        }
+        let _pinned = try!(db.pin_files().map_err(rocksdb_error));
+
+        let id = try!(self.backup_ids()).iter().max().map_or(1, |id| id + 1);
+        let private_dir = format!("private/{}", id);
+        try!(fs::mkdir_recursive(&self.dir.join(private_dir.as_slice()), io::USER_RWX));
+        let mut files = Vec::new();
+
+        // The MANIFEST is copied first: every table file it references exists until file
+        // deletions are re-enabled, and data flushed after the copy remains in the logs.
+        let private_path = self.dir.join(private_dir.as_slice());
+        let (current, manifest, size, checksum) = try!(copy_manifest(db, &private_path));
+        files.push(BackupFile { path: format!("{}/{}", private_dir, manifest),
+                                size: size,
+                                checksum: checksum });
+
+        let mut shared_files = HashSet::new();
+        for id in try!(self.backup_ids()).into_iter() {
+            for file in try!(self.read_meta(id)).into_iter() {
+                shared_files.insert(file.path);
+            }
+        }
+        for table_file in try!(list_table_files(db)).iter() {
+            let (size, checksum) = try!(file_checksum(table_file));
+            let file = BackupFile { path: format!("shared/{}_{:016x}_{}.sst",
+                                                  table_file.filestem_str().unwrap(),
+                                                  checksum,
+                                                  size),
+                                    size: size,
+                                    checksum: checksum };
+            if !shared_files.contains(&file.path) {
+                let (size, checksum) = try!(self.link_or_copy(table_file, file.path.as_slice()));
+                try!(check_file(&file, size, checksum));
+            }
+            files.push(file);
+        }
+
+        for private_file in try!(list_private_files(db)).iter() {
+            let path = format!("{}/{}", private_dir, private_file.filename_str().unwrap());
+            let (size, checksum) = try!(copy_file(private_file, &self.dir.join(path.as_slice())));
+            files.push(BackupFile { path: path, size: size, checksum: checksum });
+        }
+
+        // CURRENT is written last, and refers to the copied MANIFEST.
+        let path = format!("{}/CURRENT", private_dir);
+        let mut file = try!(io::File::create(&self.dir.join(path.as_slice())));
+        try!(file.write_str(current.as_slice()));
+        try!(file.fsync());
+        files.push(BackupFile { path: path,
+                                size: current.len() as u64,
+                                checksum: update_checksum(FNV_OFFSET, current.as_bytes()) });
+
+        try!(self.write_meta(id, files.as_slice()));
+        Ok(id)
+    }
+
+    /// Get a summary of every backup, in order of id.
+    pub fn backups(&self) -> io::IoResult<Vec<BackupInfo>> {
+        let mut backups = Vec::new();
+        for id in try!(self.backup_ids()).into_iter() {
+            let files = try!(self.read_meta(id));
+            backups.push(BackupInfo { id: id,
+                                      size: files.iter().fold(0, |size, file| size + file.size),
+                                      num_files: files.len() });
+        }
+        Ok(backups)
+    }
+
+    /// Delete a backup, along with any shared table files which no other backup holds.
+    pub fn delete_backup(&self, id: u32) -> io::IoResult<()> {
+        try!(fs::unlink(&self.meta_path(id)));
+        self.garbage_collect()
+    }
+
+    /// Delete all but the `num_backups_to_keep` most recent backups.
+    pub fn purge_old_backups(&self, num_backups_to_keep: uint) -> io::IoResult<()> {
+        let ids = try!(self.backup_ids());
+        if ids.len() > num_backups_to_keep {
+            for &id in ids.slice_to(ids.len() - num_backups_to_keep).iter() {
+                try!(fs::unlink(&self.meta_path(id)));
+            }
+        }
+        self.garbage_collect()
+    }
+
+    /// Check the size and checksum of every file in a backup.
+    pub fn verify_backup(&self, id: u32) -> io::IoResult<()> {
+        for file in try!(self.read_meta(id)).iter() {
+            let (size, checksum) = try!(file_checksum(&self.dir.join(file.path.as_slice())));
+            try!(check_file(file, size, checksum));
+        }
+        Ok(())
+    }
+
+    /// Restore a backup into a new database directory. The write ahead logs are restored into the
+    /// database directory, so the restored database must be opened without a separate WAL
+    /// directory. Fails if the directory already holds a database.
+    pub fn restore_backup(&self, id: u32, db_dir: &Path) -> io::IoResult<()> {
+        if fs::stat(&db_dir.join("CURRENT")).is_ok() {
+            return Err(io::IoError { kind: io::PathAlreadyExists,
+                                     desc: "a database already exists in the restore directory",
+                                     detail: Some(db_dir.display().to_string()) });
+        }
+        let files = try!(self.read_meta(id));
+        try!(fs::mkdir_recursive(db_dir, io::USER_RWX));
+
+        // CURRENT is restored last, so that an interrupted restore does not leave behind a
+        // directory which looks like a database.
+        let (current, files) = files.partition(|file| file.path.as_slice().ends_with("/CURRENT"));
+        for file in files.iter().chain(current.iter()) {
+            let (size, checksum) = try!(copy_file(&self.dir.join(file.path.as_slice()),
+                                                  &db_dir.join(db_file_name(file).as_slice())));
+            try!(check_file(file, size, checksum));
+        }
+        Ok(())
+    }
+
+    /// Restore the most recent backup into a new database directory. See `restore_backup`.
+    pub fn restore_latest_backup(&self, db_dir: &Path) -> io::IoResult<()> {
+        match try!(self.backup_ids()).last() {
+            Some(&id) => self.restore_backup(id, db_dir),
+            None => Err(io::IoError { kind: io::FileNotFound,
+                                      desc: "no backups",
+                                      detail: Some(self.dir.display().to_string()) })
+        }
+    }
+
+    /// Hard link or copy a table file into the backup directory, returning its size and checksum.
+    fn link_or_copy(&self, table_file: &Path, path: &str) -> io::IoResult<(u64, u64)> {
+        let target = self.dir.join(path);
+        // A leftover file from an incomplete backup may be a hard link to a live table file, so
+        // it must be unlinked rather than overwritten.
+        if fs::stat(&target).is_ok() {
+            try!(fs::unlink(&target));
+        }
+        if self.hard_link && fs::link(table_file, &target).is_ok() {
+            file_checksum(&target)
+        } else {
+            copy_file(table_file, &target)
+        }
+    }
+
+    /// Delete shared table files which are not held by any backup, and the private directories of
+    /// deleted or incomplete backups.
+    fn garbage_collect(&self) -> io::IoResult<()> {
+        let ids = try!(self.backup_ids());
+        let mut referenced = HashSet::new();
+        for &id in ids.iter() {
+            for file in try!(self.read_meta(id)).into_iter() {
+                referenced.insert(file.path);
+            }
+        }
+        for table_file in try!(fs::readdir(&self.dir.join("shared"))).iter() {
+            let path = format!("shared/{}", table_file.filename_str().unwrap());
+            if !referenced.contains(&path) {
+                try!(fs::unlink(table_file));
+            }
+        }
+        for private_dir in try!(fs::readdir(&self.dir.join("private"))).iter() {
+            let id: Option<u32> = private_dir.filename_str().and_then(from_str);
+            if !id.map_or(false, |id| ids.contains(&id)) {
+                try!(fs::rmdir_recursive(private_dir));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the ids of the complete backups, in increasing order.
+    fn backup_ids(&self) -> io::IoResult<Vec<u32>> {
+        let mut ids: Vec<u32> = try!(fs::readdir(&self.dir.join("meta")))
+                                    .iter()
+                                    .filter_map(|path| path.filename_str().and_then(from_str))
+                                    .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn meta_path(&self, id: u32) -> Path {
+        self.dir.join(format!("meta/{}", id).as_slice())
+    }
+
+    /// Write the metadata of a backup. The metadata is written to a temporary file and renamed,
+    /// so that incomplete backups have no metadata.
+    fn write_meta(&self, id: u32, files: &[BackupFile]) -> io::IoResult<()> {
+        let tmp_path = self.dir.join(format!("meta/{}.tmp", id).as_slice());
+        let mut meta = try!(io::File::create(&tmp_path));
+        for file in files.iter() {
+            try!(writeln!(meta, "{} {} {:016x}", file.path, file.size, file.checksum));
+        }
+        try!(meta.fsync());
+        fs::rename(&tmp_path, &self.meta_path(id))
+    }
+
+    /// Read the metadata of a backup.
+    fn read_meta(&self, id: u32) -> io::IoResult<Vec<BackupFile>> {
+        let text = try!(io::File::open(&self.meta_path(id)).read_to_string());
+        let mut files = Vec::new();
+        for line in text.as_slice().lines() {
+            let fields: Vec<&str> = line.words().collect();
+            let file = if fields.len() == 3 {
+                match (from_str(fields[1]), from_str_radix(fields[2], 16)) {
+                    (Some(size), Some(checksum)) => Some(BackupFile { path: fields[0].to_string(),
+                                                                      size: size,
+                                                                      checksum: checksum }),
+                    _ => None
+                }
+            } else {
+                None
+            };
+            match file {
+                Some(file) => files.push(file),
+                None => return Err(io::IoError { kind: io::InvalidInput,
+                                                 desc: "malformed backup metadata",
+                                                 detail: Some(line.to_string()) })
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Get the name of a backup file in the database directory. Shared table files are named
+/// `<number>_<checksum>_<size>.sst` in the backup directory.
+fn db_file_name(file: &BackupFile) -> String {
+    let name = Path::new(file.path.as_slice()).filename_str().unwrap().to_string();
+    if file.path.as_slice().starts_with("shared/") {
+        format!("{}.sst", name.as_slice().split('_').next().unwrap())
+    } else {
+        name
+    }
+}
+
+/// Check the size and checksum of a file against the backup metadata.
+fn check_file(file: &BackupFile, size: u64, checksum: u64) -> io::IoResult<()> {
+    if size != file.size || checksum != file.checksum {
+        Err(io::IoError { kind: io::OtherIoError,
+                          desc: "backup file is corrupt",
+                          detail: Some(file.path.clone()) })
+    } else {
+        Ok(())
+    }
+}
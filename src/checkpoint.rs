@@ -0,0 +1,71 @@
+//! Checkpoints of RocksDB databases.
+
+use std::io;
+use std::io::fs;
+
+use super::{Database, FlushOptions};
+use super::files::{copy_file, copy_manifest, list_private_files, list_table_files, rocksdb_error};
+
+impl Database {
+
+    /// Create a checkpoint of the database: a copy of its current state in a new directory, which
+    /// can be opened as a separate database. The memtables are flushed first, and table files are
+    /// hard linked into the checkpoint when it is on the same file system as the database, so
+    /// creating a checkpoint is fast and initially takes little space.
+    ///
+    /// The checkpoint is built in a sibling directory with a `.tmp` suffix, which is renamed into
+    /// place once the checkpoint is complete. If the checkpoint fails, the `.tmp` directory is
+    /// removed and the checkpoint directory is not created.
+    pub fn checkpoint(&self, checkpoint_dir: &Path) -> Result<(), String> {
+        checkpoint(self, checkpoint_dir).map_err(|error| error.to_string())
+    }
+}
+
+fn checkpoint(db: &Database, checkpoint_dir: &Path) -> io::IoResult<()> {
+    if fs::stat(checkpoint_dir).is_ok() {
+        return Err(io::IoError { kind: io::PathAlreadyExists,
+                                 desc: "the checkpoint directory already exists",
+                                 detail: Some(checkpoint_dir.display().to_string()) });
+    }
+    let staging_dir = match checkpoint_dir.filename_str() {
+        Some(name) => checkpoint_dir.with_filename(format!("{}.tmp", name)),
+        None => return Err(io::IoError { kind: io::InvalidInput,
+                                         desc: "the checkpoint directory has no file name",
+                                         detail: Some(checkpoint_dir.display().to_string()) })
+    };
+    // A staging directory may be left behind by a process which crashed during a checkpoint.
+    if fs::stat(&staging_dir).is_ok() {
+        try!(fs::rmdir_recursive(&staging_dir));
+    }
+    try!(db.flush(&FlushOptions::new()).map_err(rocksdb_error));
+    let _pinned = try!(db.pin_files().map_err(rocksdb_error));
+    try!(fs::mkdir_recursive(&staging_dir, io::USER_RWX));
+
+    let result = copy_database(db, &staging_dir).and_then(|()| {
+        fs::rename(&staging_dir, checkpoint_dir)
+    });
+    if result.is_err() {
+        let _ = fs::rmdir_recursive(&staging_dir);
+    }
+    result
+}
+
+/// Copy the files of the database into a directory, so that it can be opened as a database. File
+/// deletions must be disabled.
+fn copy_database(db: &Database, dir: &Path) -> io::IoResult<()> {
+    let (current, _, _, _) = try!(copy_manifest(db, dir));
+    for table_file in try!(list_table_files(db)).iter() {
+        let target = dir.join(table_file.filename_str().unwrap());
+        if fs::link(table_file, &target).is_err() {
+            try!(copy_file(table_file, &target));
+        }
+    }
+    for private_file in try!(list_private_files(db)).iter() {
+        try!(copy_file(private_file, &dir.join(private_file.filename_str().unwrap())));
+    }
+
+    // CURRENT is written last, and refers to the copied MANIFEST.
+    let mut file = try!(io::File::create(&dir.join("CURRENT")));
+    try!(file.write_str(current.as_slice()));
+    file.fsync()
+}
@@ -173,11 +173,12 @@ extern {
     /* Returns NULL if property name is unknown.
        Else returns a pointer to a malloc()-ed null-terminated value. */
     pub fn rocksdb_property_value(database: *mut rocksdb_t,
-                                  property: *const c_char);
+                                  property: *const c_char)
+                                  -> *mut c_char;
     pub fn rocksdb_property_value_cf(database: *mut rocksdb_t,
                                      column_family: *mut rocksdb_column_family_handle_t,
                                      property: *const c_char)
-                                     -> *mut char;
+                                     -> *mut c_char;
     pub fn rocksdb_approximate_sizes(database: *mut rocksdb_t,
                                      num_ranges: c_int,
                                      range_start_key: *const *const c_char, range_start_key_len: *const size_t,
@@ -190,8 +191,8 @@ extern {
                                         range_limit_key: *const *const c_char, range_limit_key_len: *const size_t,
                                         sizes: *mut u64);
     pub fn rocksdb_compact_range(database: *mut rocksdb_t,
-                                 start_key: *const c_char, start_key_len: *const size_t,
-                                 limit_key: *const c_char, limit_key_len: *const size_t);
+                                 start_key: *const c_char, start_key_len: size_t,
+                                 limit_key: *const c_char, limit_key_len: size_t);
     pub fn rocksdb_compact_range_cf(database: *mut rocksdb_t,
                                     column_family: *mut rocksdb_column_family_handle_t,
                                     start_key: *const c_char, start_key_len: size_t,
@@ -202,6 +203,10 @@ extern {
     pub fn rocksdb_flush(database: *mut rocksdb_t,
                          options: *const rocksdb_flushoptions_t,
                          errptr: *mut *mut c_char);
+    pub fn rocksdb_flush_cf(database: *mut rocksdb_t,
+                            options: *const rocksdb_flushoptions_t,
+                            column_family: *mut rocksdb_column_family_handle_t,
+                            errptr: *mut *mut c_char);
     pub fn rocksdb_disable_file_deletions(database: *mut rocksdb_t,
                                           errptr: *mut *mut c_char);
     pub fn rocksdb_enable_file_deletions(database: *mut rocksdb_t,
@@ -367,6 +372,7 @@ extern {
                                                                          level_values: *mut c_int,
                                                                          num_levels: size_t);
     pub fn rocksdb_options_enable_statistics(options: *mut rocksdb_options_t);
+    pub fn rocksdb_options_statistics_get_string(options: *mut rocksdb_options_t) -> *mut c_char;
     pub fn rocksdb_options_set_max_write_buffer_number(options: *mut rocksdb_options_t,
                                                        max_write_buffer_number: c_int);
     pub fn rocksdb_options_set_min_write_buffer_number_to_merge(options: *mut rocksdb_options_t,
@@ -449,18 +455,17 @@ extern {
                                                         set_source_compaction_factor: c_int);
     pub fn rocksdb_options_prepare_for_bulk_load(options: *mut rocksdb_options_t);
     pub fn rocksdb_options_set_memtable_vector_rep(options: *mut rocksdb_options_t);
-    // TODO: figure out what these options are
     pub fn rocksdb_options_set_hash_skip_list_rep(options: *mut rocksdb_options_t,
-                                                  val1: size_t,
-                                                  val2: c_int,
-                                                  val3: c_int );
+                                                  bucket_count: size_t,
+                                                  skiplist_height: c_int,
+                                                  skiplist_branching_factor: c_int);
     pub fn rocksdb_options_set_hash_link_list_rep(options: *mut rocksdb_options_t,
-                                                  val: size_t);
+                                                  bucket_count: size_t);
     pub fn rocksdb_options_set_plain_table_factory(options: *mut rocksdb_options_t,
-                                                   val1: c_uint,
-                                                   val2: c_int,
-                                                   val3: c_double,
-                                                   val4: size_t);
+                                                   user_key_len: c_uint,
+                                                   bloom_bits_per_key: c_int,
+                                                   hash_table_ratio: c_double,
+                                                   index_sparseness: size_t);
     pub fn rocksdb_options_set_min_level_to_compress(options: *mut rocksdb_options_t,
                                                      min_level_to_compress: c_int);
     pub fn rocksdb_options_set_memtable_prefix_bloom_bits(options: *mut rocksdb_options_t,
@@ -479,6 +484,34 @@ extern {
                                                       inplace_update_support: c_uchar);
     pub fn rocksdb_options_set_inplace_update_num_locks(options: *mut rocksdb_options_t,
                                                         inplace_update_num_locks: size_t);
+    pub fn rocksdb_options_set_compaction_style(options: *mut rocksdb_options_t,
+                                                compaction_style: c_int);
+    pub fn rocksdb_options_set_universal_compaction_options(options: *mut rocksdb_options_t,
+                                                            universal_compaction_options: *mut rocksdb_universal_compaction_options_t);
+    pub fn rocksdb_options_set_fifo_compaction_options(options: *mut rocksdb_options_t,
+                                                       fifo_compaction_options: *mut rocksdb_fifo_compaction_options_t);
+
+    /* Universal compaction options */
+    pub fn rocksdb_universal_compaction_options_create() -> *mut rocksdb_universal_compaction_options_t;
+    pub fn rocksdb_universal_compaction_options_set_size_ratio(options: *mut rocksdb_universal_compaction_options_t,
+                                                               size_ratio: c_int);
+    pub fn rocksdb_universal_compaction_options_set_min_merge_width(options: *mut rocksdb_universal_compaction_options_t,
+                                                                    min_merge_width: c_int);
+    pub fn rocksdb_universal_compaction_options_set_max_merge_width(options: *mut rocksdb_universal_compaction_options_t,
+                                                                    max_merge_width: c_int);
+    pub fn rocksdb_universal_compaction_options_set_max_size_amplification_percent(options: *mut rocksdb_universal_compaction_options_t,
+                                                                                   max_size_amplification_percent: c_int);
+    pub fn rocksdb_universal_compaction_options_set_compression_size_percent(options: *mut rocksdb_universal_compaction_options_t,
+                                                                             compression_size_percent: c_int);
+    pub fn rocksdb_universal_compaction_options_set_stop_style(options: *mut rocksdb_universal_compaction_options_t,
+                                                               stop_style: c_int);
+    pub fn rocksdb_universal_compaction_options_destroy(options: *mut rocksdb_universal_compaction_options_t);
+
+    /* FIFO compaction options */
+    pub fn rocksdb_fifo_compaction_options_create() -> *mut rocksdb_fifo_compaction_options_t;
+    pub fn rocksdb_fifo_compaction_options_set_max_table_files_size(options: *mut rocksdb_fifo_compaction_options_t,
+                                                                    max_table_files_size: u64);
+    pub fn rocksdb_fifo_compaction_options_destroy(options: *mut rocksdb_fifo_compaction_options_t);
 
     /* Comparator */
     pub fn rocksdb_comparator_create(state: *mut c_void,
@@ -541,24 +574,52 @@ extern {
     pub fn rocksdb_flushoptions_set_wait(options: *mut rocksdb_flushoptions_t,
                                          wait: c_uchar);
 
+    /* Live files */
+    pub fn rocksdb_livefiles_count(live_files: *const rocksdb_livefiles_t) -> c_int;
+    pub fn rocksdb_livefiles_column_family_name(live_files: *const rocksdb_livefiles_t,
+                                                index: c_int)
+                                                -> *const c_char;
+    pub fn rocksdb_livefiles_name(live_files: *const rocksdb_livefiles_t,
+                                  index: c_int)
+                                  -> *const c_char;
+    pub fn rocksdb_livefiles_level(live_files: *const rocksdb_livefiles_t,
+                                   index: c_int)
+                                   -> c_int;
+    pub fn rocksdb_livefiles_size(live_files: *const rocksdb_livefiles_t,
+                                  index: c_int)
+                                  -> size_t;
+    pub fn rocksdb_livefiles_smallestkey(live_files: *const rocksdb_livefiles_t,
+                                         index: c_int,
+                                         size: *mut size_t)
+                                         -> *const c_char;
+    pub fn rocksdb_livefiles_largestkey(live_files: *const rocksdb_livefiles_t,
+                                        index: c_int,
+                                        size: *mut size_t)
+                                        -> *const c_char;
+    pub fn rocksdb_livefiles_destroy(live_files: *const rocksdb_livefiles_t);
+
+    /* Filter policy */
+    pub fn rocksdb_filterpolicy_create_bloom(bits_per_key: c_int) -> *mut rocksdb_filterpolicy_t;
+    pub fn rocksdb_filterpolicy_destroy(filter_policy: *mut rocksdb_filterpolicy_t);
+
     /* Cache */
     pub fn rocksdb_cache_create_lru(capacity: size_t) -> *mut rocksdb_cache_t;
     pub fn rocksdb_cache_destroy(cache: *mut rocksdb_cache_t);
 
     /* SliceTransform */
 
-    pub fn rocksdb_slicetranform_create(state: *mut c_void,
-                                        destructor: extern fn(*mut c_void),
-                                        transform: extern fn(*mut c_void,           // state
-                                                             *const c_char, size_t, // key
-                                                             *mut size_t)           // prefix length
-                                                             -> *mut c_char,        // result
-                                        in_domain: extern fn(*mut c_void,           // state
+    pub fn rocksdb_slicetransform_create(state: *mut c_void,
+                                         destructor: extern fn(*mut c_void),
+                                         transform: extern fn(*mut c_void,           // state
+                                                              *const c_char, size_t, // key
+                                                              *mut size_t)           // prefix length
+                                                              -> *mut c_char,        // result
+                                         in_domain: extern fn(*mut c_void,           // state
+                                                              *const c_char, size_t) // key
+                                                              -> c_uchar,            // result
+                                         in_range: extern fn(*mut c_void,           // state
                                                              *const c_char, size_t) // key
-                                                             -> c_uchar,           // result
-                                        in_range: extern fn(*mut c_void,           // state
-                                                            *const c_char, size_t) // key
-                                                            -> c_uchar,            // result
-                                        name: extern fn(*mut c_void) -> *const c_char)
-                                        -> *mut rocksdb_slicetransform_t;
+                                                             -> c_uchar,            // result
+                                         name: extern fn(*mut c_void) -> *const c_char)
+                                         -> *mut rocksdb_slicetransform_t;
 }
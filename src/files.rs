@@ -0,0 +1,118 @@
+//! Copying and checksumming the files of a running database, shared by backups and checkpoints.
+
+use std::io;
+use std::io::fs;
+
+use super::Database;
+
+pub static FNV_OFFSET: u64 = 0xcbf29ce484222325;
+static FNV_PRIME: u64 = 0x100000001b3;
+
+/// The number of attempts made to copy a MANIFEST file which is being written to concurrently.
+static MANIFEST_COPY_ATTEMPTS: uint = 10;
+
+/// Copy the current MANIFEST of the database into a directory. Returns the contents of the
+/// CURRENT file which refers to it, its name, its size and its checksum.
+pub fn copy_manifest(db: &Database, dir: &Path) -> io::IoResult<(String, String, u64, u64)> {
+    let current_path = db.path().join("CURRENT");
+    for _ in range(0, MANIFEST_COPY_ATTEMPTS) {
+        let current = try!(io::File::open(&current_path).read_to_string());
+        let name = current.as_slice().trim().to_string();
+        let manifest = db.path().join(name.as_slice());
+        let target = dir.join(name.as_slice());
+        let (size, checksum) = try!(copy_file(&manifest, &target));
+
+        // The MANIFEST is appended to by flushes and compactions, and replaced when it grows too
+        // large. Retry if either happened during the copy.
+        let unchanged = try!(io::File::open(&current_path).read_to_string()) == current
+                     && try!(fs::stat(&manifest)).size == size;
+        if unchanged {
+            return Ok((current, name, size, checksum));
+        }
+        try!(fs::unlink(&target));
+    }
+    Err(io::IoError { kind: io::ResourceUnavailable,
+                      desc: "the MANIFEST changed during every attempt to copy it",
+                      detail: None })
+}
+
+/// List the live table files of the database. Must be called after the MANIFEST is copied, with
+/// file deletions disabled, so that every table file which the copy refers to is listed. Table
+/// files which are still being written are not live, and are left out. Fails if a live table
+/// file is not in the database directory.
+pub fn list_table_files(db: &Database) -> io::IoResult<Vec<Path>> {
+    let mut table_files = Vec::new();
+    for live_file in db.live_files().iter() {
+        let path = db.path().join(live_file.name.as_slice().trim_left_chars('/'));
+        if fs::stat(&path).is_err() {
+            return Err(io::IoError { kind: io::FileNotFound,
+                                     desc: "a live table file is not in the database directory",
+                                     detail: Some(live_file.name.clone()) });
+        }
+        table_files.push(path);
+    }
+    Ok(table_files)
+}
+
+/// List the files of the database other than table files, the MANIFEST and CURRENT which must be
+/// copied: the write ahead logs, and options files if there are any.
+pub fn list_private_files(db: &Database) -> io::IoResult<Vec<Path>> {
+    let mut files = try!(list_files(&db.wal_dir, "log"));
+    for path in try!(fs::readdir(db.path())).into_iter() {
+        if path.filename_str().map_or(false, |name| name.starts_with("OPTIONS")) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// List the files in a directory with the provided extension.
+fn list_files(dir: &Path, extension: &str) -> io::IoResult<Vec<Path>> {
+    Ok(try!(fs::readdir(dir)).into_iter()
+                             .filter(|path| path.extension_str() == Some(extension))
+                             .collect())
+}
+
+/// Update a 64-bit FNV-1a checksum with the provided bytes.
+pub fn update_checksum(checksum: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(checksum, |checksum, &byte| (checksum ^ byte as u64) * FNV_PRIME)
+}
+
+/// Copy a file, returning its size and checksum.
+pub fn copy_file(from: &Path, to: &Path) -> io::IoResult<(u64, u64)> {
+    let mut writer = try!(io::File::create(to));
+    let result = try!(read_file(from, |bytes| writer.write(bytes)));
+    try!(writer.fsync());
+    Ok(result)
+}
+
+/// Get the size and checksum of a file.
+pub fn file_checksum(path: &Path) -> io::IoResult<(u64, u64)> {
+    read_file(path, |_| Ok(()))
+}
+
+/// Read a file in chunks, returning its size and checksum.
+fn read_file(path: &Path, f: |&[u8]| -> io::IoResult<()>) -> io::IoResult<(u64, u64)> {
+    let mut reader = try!(io::File::open(path));
+    let mut buf = Vec::from_elem(64 << 10, 0u8);
+    let mut size = 0;
+    let mut checksum = FNV_OFFSET;
+    loop {
+        match reader.read(buf.as_mut_slice()) {
+            Ok(len) => {
+                let bytes = buf.slice_to(len);
+                try!(f(bytes));
+                size += len as u64;
+                checksum = update_checksum(checksum, bytes);
+            },
+            Err(ref error) if error.kind == io::EndOfFile => break,
+            Err(error) => return Err(error)
+        }
+    }
+    Ok((size, checksum))
+}
+
+/// Convert a RocksDB error message to an I/O error.
+pub fn rocksdb_error(error: String) -> io::IoError {
+    io::IoError { kind: io::OtherIoError, desc: "RocksDB error", detail: Some(error) }
+}
@@ -0,0 +1,200 @@
+//! Forwarding of the RocksDB info log to the `log` crate.
+//!
+//! The RocksDB C API has no way of installing a logger implemented in Rust, so instead the info
+//! log file written by RocksDB is followed by a background task, and each line is emitted as a log
+//! record with the module path `rocksdb`. Lines which RocksDB prefixes with `[WARN]` are forwarded
+//! at the warn level, lines prefixed with `[ERROR]` or `[FATAL]` at the error level, and all other
+//! lines at the info level. RocksDB continues to write the info log file.
+
+use std::io;
+use std::io::fs;
+use std::io::{File, IoResult};
+use std::io::timer;
+use std::os;
+use std::str;
+use std::time::Duration;
+
+use log;
+
+/// The interval at which the info log file is checked for new lines.
+static POLL_INTERVAL_MS: i64 = 100;
+
+static LOCATION: log::LogLocation = log::LogLocation { module_path: "rocksdb",
+                                                       file: file!(),
+                                                       line: line!() };
+
+/// The longest prefix RocksDB writes in an info log file name: its buffer holds 500 bytes, less the
+/// `_LOG` suffix and a terminating nul.
+static MAX_PREFIX_LEN: uint = 500 - 5;
+
+/// Get the path of the info log file of the database at `db_path`. When `db_log_dir` is set, the
+/// file name is prefixed with the flattened absolute path of the database.
+pub fn info_log_path(db_path: &Path, db_log_dir: Option<&Path>) -> Path {
+    match db_log_dir {
+        None => db_path.join("LOG"),
+        Some(db_log_dir) => {
+            let db_path = os::make_absolute(db_path).unwrap_or(db_path.clone());
+            db_log_dir.join(format!("{}_LOG", info_log_prefix(db_path.as_vec())))
+        }
+    }
+}
+
+/// Flatten the absolute path of a database into an info log file name prefix, as RocksDB's
+/// `GetInfoLogPrefix` does. Bytes other than ASCII letters, digits, `-`, `.` and `_` are replaced
+/// with `_`, except at the start of the path, where they are dropped.
+fn info_log_prefix(db_path: &[u8]) -> String {
+    let mut prefix = String::new();
+    for (i, &b) in db_path.iter().enumerate() {
+        if prefix.len() >= MAX_PREFIX_LEN {
+            break;
+        }
+        match b {
+            b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' | b'-' | b'.' | b'_' => prefix.push(b as char),
+            _ if i > 0 => prefix.push('_'),
+            _ => ()
+        }
+    }
+    prefix
+}
+
+/// Split a line of the info log into the log level and the message, removing the timestamp and
+/// thread id which RocksDB writes at the start of each line.
+pub fn parse_line(line: &str) -> (u32, &str) {
+    let mut message = line.trim_right();
+    if message.slice_to(message.find(' ').unwrap_or(message.len())).contains_char('/') {
+        message = skip_word(skip_word(message));
+    }
+    for &(prefix, level) in [("[WARN] ", log::WARN),
+                             ("[ERROR] ", log::ERROR),
+                             ("[FATAL] ", log::ERROR)].iter() {
+        if message.starts_with(prefix) {
+            return (level, message.slice_from(prefix.len()));
+        }
+    }
+    (log::INFO, message)
+}
+
+fn skip_word(s: &str) -> &str {
+    s.find(' ').map_or("", |idx| s.slice_from(idx + 1))
+}
+
+/// Follows the info log file of a database, forwarding new lines until dropped. Lines written
+/// before the forwarder is dropped are forwarded before the drop returns.
+pub struct InfoLogForwarder {
+    stop: Sender<()>,
+    stopped: Receiver<()>
+}
+
+impl InfoLogForwarder {
+
+    /// Start forwarding the info log file at `path`. If `logger` is set, it is installed as the
+    /// logger of the forwarding task, instead of the default logger.
+    pub fn start(path: Path, logger: Option<Box<log::Logger + Send>>) -> InfoLogForwarder {
+        let (stop, stop_receiver) = channel();
+        let (stopped_sender, stopped) = channel();
+        spawn(proc() {
+            match logger {
+                Some(logger) => { log::set_logger(logger); },
+                None => ()
+            }
+            let mut follower = Follower { path: path, file: None, inode: 0, partial: Vec::new() };
+            loop {
+                let stopping = stop_receiver.try_recv().is_ok();
+                match follower.forward(stopping) {
+                    Ok(()) => (),
+                    Err(error) => {
+                        error!("Unable to read the RocksDB info log {}: {}",
+                               follower.path.display(), error);
+                        follower.file = None;
+                    }
+                }
+                if stopping {
+                    break;
+                }
+                timer::sleep(Duration::milliseconds(POLL_INTERVAL_MS));
+            }
+            stopped_sender.send(());
+        });
+        InfoLogForwarder { stop: stop, stopped: stopped }
+    }
+}
+
+impl Drop for InfoLogForwarder {
+    fn drop(&mut self) {
+        self.stop.send(());
+        self.stopped.recv();
+    }
+}
+
+struct Follower {
+    path: Path,
+    file: Option<File>,
+    /// The inode of the open file. RocksDB rolls the info log by renaming the file and creating a
+    /// new one in its place.
+    inode: u64,
+    /// The bytes read after the last complete line.
+    partial: Vec<u8>
+}
+
+impl Follower {
+
+    /// Forward the complete lines appended to the info log since the last call, and the trailing
+    /// partial line if `flush` is true.
+    fn forward(&mut self, flush: bool) -> IoResult<()> {
+        let inode = match fs::stat(&self.path) {
+            Ok(stat) => stat.unstable.inode,
+            Err(ref error) if error.kind == io::FileNotFound => return Ok(()),
+            Err(error) => return Err(error)
+        };
+        if self.file.is_none() || inode != self.inode {
+            try!(self.read_lines());
+            self.emit_partial();
+            self.file = Some(try!(File::open(&self.path)));
+            self.inode = inode;
+        }
+        try!(self.read_lines());
+        if flush {
+            self.emit_partial();
+        }
+        Ok(())
+    }
+
+    /// Read to the end of the open file, forwarding each complete line.
+    fn read_lines(&mut self) -> IoResult<()> {
+        let mut buf = [0u8, ..4096];
+        loop {
+            let read = match self.file {
+                None => return Ok(()),
+                Some(ref mut file) => match file.read(&mut buf) {
+                    Ok(read) => read,
+                    Err(ref error) if error.kind == io::EndOfFile => return Ok(()),
+                    Err(error) => return Err(error)
+                }
+            };
+            self.partial.push_all(buf.slice_to(read));
+            let end = match self.partial.iter().rposition(|&b| b == b'\n') {
+                Some(end) => end,
+                None => continue
+            };
+            for line in self.partial.slice_to(end).split(|&b| b == b'\n') {
+                emit(line);
+            }
+            self.partial = self.partial.slice_from(end + 1).to_vec();
+        }
+    }
+
+    fn emit_partial(&mut self) {
+        if !self.partial.is_empty() {
+            emit(self.partial.as_slice());
+            self.partial.clear();
+        }
+    }
+}
+
+fn emit(line: &[u8]) {
+    let line = str::from_utf8_lossy(line);
+    let (level, message) = parse_line(line.as_slice());
+    if !message.is_empty() && log::mod_enabled(level, "rocksdb") {
+        format_args!(|args| log::log(level, &LOCATION, args), "{}", message)
+    }
+}
@@ -1,21 +1,29 @@
 #![feature(phase, globs, unsafe_destructor)]
 #[phase(plugin, link)] extern crate log;
 extern crate libc;
+extern crate serialize;
 
-use libc::c_void;
+use libc::{c_int, c_void, size_t};
 use std::c_str::CString;
 use std::c_vec::CVec;
 use std::collections::HashMap;
 use std::kinds::marker;
+use std::from_str::FromStr;
 use std::path::posix::Path;
-use std::{io, mem, ptr, raw, slice, vec};
+use std::time::Duration;
+use std::{fmt, i32, i64, io, mem, ptr, raw, slice, u32, u64, vec};
 
 use ffi::*;
 
 #[cfg(test)]
 mod tests;
+pub mod backup;
+mod checkpoint;
 mod ffi;
+mod files;
+mod info_log;
 pub mod merge_operators;
+pub mod options_file;
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 //// Database
@@ -23,8 +31,13 @@ pub mod merge_operators;
 
 pub struct Database {
     database: *mut rocksdb_t,
+    path: Path,
+    wal_dir: Path,
     column_families: HashMap<String, ColumnFamily>,
-    _column_family_options: Vec<ColumnFamilyOptions>
+    db_options: DatabaseOptions,
+    _column_family_options: Vec<ColumnFamilyOptions>,
+    /// Dropped after the database is closed, so that the final info log lines are forwarded.
+    _info_log: Option<info_log::InfoLogForwarder>
 }
 
 impl Drop for Database {
@@ -72,6 +85,11 @@ impl Database {
         let num_cfs = column_family_options.len();
         let (cf_names, cf_options) = vec::unzip(column_family_options.into_iter());
 
+        for (cf_name, options) in cf_names.iter().zip(cf_options.iter()) {
+            try!(options.validate(&db_options)
+                        .map_err(|error| format!("column family {}: {}", cf_name, error)));
+        }
+
         // Translate the column family names to a vec of c string pointers.
         let cf_c_names: Vec<CString> = cf_names.iter()
                                                .map(|cf_name| cf_name.to_c_str())
@@ -101,15 +119,32 @@ impl Database {
                                   ColumnFamily { database: database,
                                                  column_family: *cf_ptrs.offset(i as int) }))
                             .collect();
+                let wal_dir = db_options.get("wal_dir").map_or(path.clone(), |dir| Path::new(dir));
+                let info_log = if db_options.get("forward_info_log") == Some("true") {
+                    let db_log_dir = db_options.get("db_log_dir").map(|dir| Path::new(dir));
+                    let log_file = info_log::info_log_path(path, db_log_dir.as_ref());
+                    Some(info_log::InfoLogForwarder::start(log_file, None))
+                } else {
+                    None
+                };
                 Ok(Database { database: database,
+                              path: path.clone(),
+                              wal_dir: wal_dir,
                               column_families: column_families,
-                              _column_family_options: cf_options })
+                              db_options: db_options,
+                              _column_family_options: cf_options,
+                              _info_log: info_log })
             } else {
                 Err(CString::new(error as *const i8, true).to_string())
             }
         }
     }
 
+    /// The directory holding the database.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     pub fn get_column_family<'a>(&'a self, column_family: &str) -> Option<&'a ColumnFamily> {
         self.column_families.find_equiv(column_family)
     }
@@ -118,6 +153,136 @@ impl Database {
         &self.column_families
     }
 
+    /// Flush the memtables of every column family to table files.
+    pub fn flush(&self, options: &FlushOptions) -> Result<(), String> {
+        for column_family in self.column_families.values() {
+            try!(column_family.flush(options));
+        }
+        Ok(())
+    }
+
+    /// Get the value of a database property of the default column family, or `None` if the
+    /// property is unknown. See `ColumnFamily::property`.
+    pub fn property(&self, name: &str) -> Option<String> {
+        unsafe {
+            let value = rocksdb_property_value(self.database, name.to_c_str().as_ptr());
+            if value == ptr::null_mut() {
+                None
+            } else {
+                Some(CString::new(value as *const i8, true).to_string())
+            }
+        }
+    }
+
+    /// Get the statistics collected by the database, or `None` if statistics were not enabled with
+    /// `DatabaseOptions::enable_statistics`.
+    pub fn statistics(&self) -> Option<Statistics> {
+        unsafe {
+            let value = rocksdb_options_statistics_get_string(self.db_options.options());
+            if value == ptr::null_mut() {
+                None
+            } else {
+                let value = CString::new(value as *const i8, true).to_string();
+                Some(Statistics::parse(value.as_slice()))
+            }
+        }
+    }
+
+    /// Get the table files which make up the current state of the database.
+    pub fn live_files(&self) -> Vec<LiveFile> {
+        unsafe {
+            let live_files = rocksdb_livefiles(self.database) as *const rocksdb_livefiles_t;
+            let files = range(0, rocksdb_livefiles_count(live_files)).map(|i| {
+                let mut smallest_key_len: u64 = 0;
+                let mut largest_key_len: u64 = 0;
+                let smallest_key = rocksdb_livefiles_smallestkey(live_files, i,
+                                                                 &mut smallest_key_len);
+                let largest_key = rocksdb_livefiles_largestkey(live_files, i,
+                                                               &mut largest_key_len);
+                let column_family = rocksdb_livefiles_column_family_name(live_files, i);
+                let name = rocksdb_livefiles_name(live_files, i);
+                LiveFile {
+                    column_family: CString::new(column_family, false).to_string(),
+                    name: CString::new(name, false).to_string(),
+                    level: rocksdb_livefiles_level(live_files, i) as uint,
+                    size: rocksdb_livefiles_size(live_files, i),
+                    smallest_key: vec::raw::from_buf(smallest_key as *const u8,
+                                                     smallest_key_len as uint),
+                    largest_key: vec::raw::from_buf(largest_key as *const u8,
+                                                    largest_key_len as uint)
+                }
+            }).collect();
+            rocksdb_livefiles_destroy(live_files);
+            files
+        }
+    }
+
+    /// Delete a table file, dropping every key it holds, including keys which also exist in
+    /// other files. This is a cheap way to drop a key range which is entirely obsolete. `start`
+    /// and `end` are the inclusive bounds of the range the caller considers obsolete; the file
+    /// is only deleted if its keys fall within the range. RocksDB only allows deleting files in
+    /// the last level of a column family, and level 0 files are never deleted, since they may
+    /// overlap newer files.
+    ///
+    /// Fails if the file is no longer live, if its keys are not within the range, or if RocksDB
+    /// declines to delete it. The file is checked before and after the deletion rather than
+    /// atomically with it, so a compaction running at the same time may remove or replace the
+    /// file in between. In that case the deletion may be reported as failed although the file is
+    /// gone, or as successful although the compaction removed it.
+    pub fn delete_file(&self, file: &LiveFile, start: &[u8], end: &[u8]) -> Result<(), String> {
+        let live = match self.live_files().into_iter().find(|live| live.name == file.name) {
+            Some(live) => live,
+            None => return Err(format!("{} is not a live file", file.name))
+        };
+        if live.level == 0 {
+            return Err(format!("{} is in level 0", file.name));
+        }
+        if live.smallest_key.as_slice() < start || live.largest_key.as_slice() > end {
+            return Err(format!("{} holds keys outside of the obsolete range", file.name));
+        }
+        unsafe { rocksdb_delete_file(self.database, file.name.to_c_str().as_ptr()) };
+        if self.live_files().iter().any(|live| live.name == file.name) {
+            return Err(format!("{} could not be deleted", file.name));
+        }
+        Ok(())
+    }
+
+    /// Prevent the database from deleting obsolete files until the returned guard is dropped,
+    /// so that the files making up the current state of the database can be copied.
+    fn pin_files<'a>(&'a self) -> Result<PinnedFiles<'a>, String> {
+        let mut error: *mut i8 = ptr::null_mut();
+        unsafe {
+            rocksdb_disable_file_deletions(self.database, &mut error);
+            if error == ptr::null_mut() {
+                Ok(PinnedFiles { database: self })
+            } else {
+                Err(CString::new(error as *const i8, true).to_string())
+            }
+        }
+    }
+
+    /// Compact the keys in the inclusive range `[start, end]` of every column family. A missing
+    /// bound extends the range to the first or last key. Deleted and overwritten values in the
+    /// range are discarded. Blocks until the compaction is complete.
+    pub fn compact_range(&self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        for column_family in self.column_families.values() {
+            column_family.compact_range(start, end);
+        }
+    }
+
+    /// Compact the keys in the inclusive range `[start, end]` of every column family on a
+    /// background task.
+    /// See `compact_range`.
+    pub fn compact_range_in_background<'a>(&'a self,
+                                           start: Option<&[u8]>,
+                                           end: Option<&[u8]>)
+                                           -> CompactionHandle<'a> {
+        let column_families: Vec<uint> = self.column_families.values()
+                                                             .map(|cf| cf.column_family as uint)
+                                                             .collect();
+        CompactionHandle::spawn(self.database, column_families, start, end)
+    }
+
     pub fn write(&self, options: &WriteOptions, write_batch: WriteBatch) -> Result<(), String> {
         let mut error: *mut i8 = ptr::null_mut();
         unsafe {
@@ -235,6 +400,441 @@ impl ColumnFamily {
             }
         }
     }
+
+    /// Get the value of a database property of the column family, or `None` if the property is
+    /// unknown. Properties are named with a `rocksdb.` prefix, for example `rocksdb.stats`. The
+    /// typed accessors below parse the values of well-known properties.
+    pub fn property(&self, name: &str) -> Option<String> {
+        unsafe {
+            let value = rocksdb_property_value_cf(self.database,
+                                                  self.column_family,
+                                                  name.to_c_str().as_ptr());
+            if value == ptr::null_mut() {
+                None
+            } else {
+                Some(CString::new(value as *const i8, true).to_string())
+            }
+        }
+    }
+
+    /// Get the value of a database property of the column family as an integer.
+    fn int_property(&self, name: &str) -> Option<u64> {
+        self.property(name).and_then(|value| from_str(value.as_slice().trim()))
+    }
+
+    /// Human readable statistics about the column family and its compactions.
+    pub fn stats(&self) -> Option<String> {
+        self.property("rocksdb.stats")
+    }
+
+    /// Human readable description of the table files of the column family, by level.
+    pub fn sstables(&self) -> Option<String> {
+        self.property("rocksdb.sstables")
+    }
+
+    /// The number of files and their total size at each level of the column family.
+    pub fn level_stats(&self) -> Option<Vec<LevelStats>> {
+        self.property("rocksdb.levelstats").and_then(|value| LevelStats::parse(value.as_slice()))
+    }
+
+    /// The number of table files at a level of the column family.
+    pub fn num_files_at_level(&self, level: uint) -> Option<u64> {
+        self.int_property(format!("rocksdb.num-files-at-level{}", level).as_slice())
+    }
+
+    /// The estimated number of keys in the column family, including the memtables.
+    pub fn estimate_num_keys(&self) -> Option<u64> {
+        self.int_property("rocksdb.estimate-num-keys")
+    }
+
+    /// The approximate size of the active memtable, in bytes.
+    pub fn cur_size_active_mem_table(&self) -> Option<u64> {
+        self.int_property("rocksdb.cur-size-active-mem-table")
+    }
+
+    /// The approximate size of the active and immutable memtables, in bytes.
+    pub fn cur_size_all_mem_tables(&self) -> Option<u64> {
+        self.int_property("rocksdb.cur-size-all-mem-tables")
+    }
+
+    /// The number of entries in the active memtable.
+    pub fn num_entries_active_mem_table(&self) -> Option<u64> {
+        self.int_property("rocksdb.num-entries-active-mem-table")
+    }
+
+    /// The number of immutable memtables which have not yet been flushed.
+    pub fn num_immutable_mem_table(&self) -> Option<u64> {
+        self.int_property("rocksdb.num-immutable-mem-table")
+    }
+
+    /// Returns true if a memtable flush is pending.
+    pub fn mem_table_flush_pending(&self) -> Option<bool> {
+        self.int_property("rocksdb.mem-table-flush-pending").map(|pending| pending != 0)
+    }
+
+    /// Returns true if at least one compaction is pending.
+    pub fn compaction_pending(&self) -> Option<bool> {
+        self.int_property("rocksdb.compaction-pending").map(|pending| pending != 0)
+    }
+
+    /// The number of background errors accumulated by the database.
+    pub fn background_errors(&self) -> Option<u64> {
+        self.int_property("rocksdb.background-errors")
+    }
+
+    /// Get the approximate size of the table files holding each key range, in bytes. A range
+    /// includes its start key but not its end key. Data held in memtables is not included, so
+    /// recent writes may not be reflected until they are flushed.
+    pub fn approximate_sizes(&self, ranges: &[(&[u8], &[u8])]) -> Vec<u64> {
+        let starts: Vec<&[u8]> = ranges.iter().map(|range| range.val0()).collect();
+        let ends: Vec<&[u8]> = ranges.iter().map(|range| range.val1()).collect();
+        let start_ptrs: Vec<*const i8> = starts.iter().map(|s| s.as_ptr() as *const i8).collect();
+        let start_lens: Vec<size_t> = starts.iter().map(|s| s.len() as size_t).collect();
+        let end_ptrs: Vec<*const i8> = ends.iter().map(|e| e.as_ptr() as *const i8).collect();
+        let end_lens: Vec<size_t> = ends.iter().map(|e| e.len() as size_t).collect();
+        let mut sizes = Vec::from_elem(ranges.len(), 0u64);
+        unsafe {
+            rocksdb_approximate_sizes_cf(self.database,
+                                         self.column_family,
+                                         ranges.len() as c_int,
+                                         start_ptrs.as_ptr(), start_lens.as_ptr(),
+                                         end_ptrs.as_ptr(), end_lens.as_ptr(),
+                                         sizes.as_mut_ptr());
+        }
+        sizes
+    }
+
+    /// Compact the keys in the inclusive range `[start, end]` of the column family. A missing bound
+    /// extends the range to the first or last key. Deleted and overwritten values in the range are
+    /// discarded. Blocks until the compaction is complete.
+    pub fn compact_range(&self, start: Option<&[u8]>, end: Option<&[u8]>) {
+        unsafe { compact_range(self.database, self.column_family, start, end) }
+    }
+
+    /// Compact the keys in the inclusive range `[start, end]` of the column family on a background
+    /// task.
+    /// See `compact_range`.
+    pub fn compact_range_in_background<'a>(&'a self,
+                                           start: Option<&[u8]>,
+                                           end: Option<&[u8]>)
+                                           -> CompactionHandle<'a> {
+        CompactionHandle::spawn(self.database, vec!(self.column_family as uint), start, end)
+    }
+
+    /// Flush the memtable of the column family to a table file.
+    pub fn flush(&self, options: &FlushOptions) -> Result<(), String> {
+        let mut error: *mut i8 = ptr::null_mut();
+        unsafe {
+            rocksdb_flush_cf(self.database,
+                             options.options(),
+                             self.column_family,
+                             (&mut error) as *mut *mut i8);
+            if error == ptr::null_mut() {
+                Ok(())
+            } else {
+                Err(CString::new(error as *const i8, true).to_string())
+            }
+        }
+    }
+}
+
+/// The table files at a level of a column family, as reported by the `rocksdb.levelstats`
+/// property.
+#[deriving(Clone, PartialEq, Show)]
+pub struct LevelStats {
+    pub level: uint,
+    pub num_files: u64,
+    /// The total size of the files, in megabytes.
+    pub size_mb: u64
+}
+
+impl LevelStats {
+
+    /// Parse the value of the `rocksdb.levelstats` property, which is a table with a two line
+    /// header followed by a line for each level.
+    fn parse(value: &str) -> Option<Vec<LevelStats>> {
+        let mut levels = Vec::new();
+        for line in value.lines().skip(2).filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.words().collect();
+            if fields.len() != 3 {
+                return None;
+            }
+            match (from_str(fields[0]), from_str(fields[1]), from_str(fields[2])) {
+                (Some(level), Some(num_files), Some(size_mb)) => {
+                    levels.push(LevelStats { level: level, num_files: num_files, size_mb: size_mb })
+                },
+                _ => return None
+            }
+        }
+        Some(levels)
+    }
+}
+
+/// A snapshot of the tickers and histograms collected by a database with statistics enabled.
+/// Tickers are counters which only increase over the lifetime of the database.
+#[deriving(Clone, PartialEq, Show)]
+pub struct Statistics {
+    tickers: HashMap<String, u64>,
+    histograms: HashMap<String, Histogram>
+}
+
+/// A histogram collected by a database with statistics enabled.
+#[deriving(Clone, PartialEq, Show)]
+pub struct Histogram {
+    pub median: f64,
+    pub percentile95: f64,
+    pub percentile99: f64,
+    /// The number of values recorded, or `None` if the RocksDB version does not report it.
+    pub count: Option<u64>,
+    /// The sum of the values recorded, or `None` if the RocksDB version does not report it.
+    pub sum: Option<u64>
+}
+
+impl Statistics {
+
+    /// Parse the statistics string of a database, which has a line for each ticker of the form
+    /// `<name> COUNT : <count>`, followed by a line for each histogram. Older versions of RocksDB
+    /// write histograms as `<name> statistics Percentiles :=> 50 : <median> 95 : <p95> 99 : <p99>`,
+    /// and newer versions as `<name> P50 : <median> P95 : <p95> P99 : <p99> ... COUNT : <count>
+    /// SUM : <sum>`. Lines in any other form are skipped.
+    fn parse(value: &str) -> Statistics {
+        let mut tickers = HashMap::new();
+        let mut histograms = HashMap::new();
+        for line in value.lines() {
+            let fields: Vec<&str> = line.words().collect();
+            if fields.len() == 4 && fields[1] == "COUNT" && fields[2] == ":" {
+                match from_str(fields[3]) {
+                    Some(count) => { tickers.insert(fields[0].to_string(), count); },
+                    None => ()
+                }
+                continue;
+            }
+            let histogram = if fields.len() >= 4 && fields[1] == "statistics"
+                                                 && fields[2] == "Percentiles"
+                                                 && fields[3] == ":=>" {
+                parse_histogram(fields.slice_from(4), "50", "95", "99")
+            } else if fields.len() >= 2 && fields[1] == "P50" {
+                parse_histogram(fields.slice_from(1), "P50", "P95", "P99")
+            } else {
+                None
+            };
+            match histogram {
+                Some(histogram) => { histograms.insert(fields[0].to_string(), histogram); },
+                None => ()
+            }
+        }
+        Statistics { tickers: tickers, histograms: histograms }
+    }
+
+    /// The value of a ticker, or `None` if the database did not report it.
+    pub fn ticker(&self, ticker: Ticker) -> Option<u64> {
+        self.tickers.find_equiv(ticker.to_string().as_slice()).map(|&count| count)
+    }
+
+    /// A histogram, or `None` if the database did not report it.
+    pub fn histogram(&self, histogram: HistogramType) -> Option<Histogram> {
+        self.histograms.find_equiv(histogram.to_string().as_slice()).map(|h| h.clone())
+    }
+
+    /// All tickers, by RocksDB name, including those which have no `Ticker` variant.
+    pub fn tickers(&self) -> &HashMap<String, u64> {
+        &self.tickers
+    }
+
+    /// All histograms, by RocksDB name, including those which have no `HistogramType` variant.
+    pub fn histograms(&self) -> &HashMap<String, Histogram> {
+        &self.histograms
+    }
+
+    /// The number of block cache hits.
+    pub fn block_cache_hits(&self) -> u64 {
+        self.ticker(TickerBlockCacheHit).unwrap_or(0)
+    }
+
+    /// The number of block cache misses.
+    pub fn block_cache_misses(&self) -> u64 {
+        self.ticker(TickerBlockCacheMiss).unwrap_or(0)
+    }
+
+    /// The number of uncompressed bytes read by gets.
+    pub fn bytes_read(&self) -> u64 {
+        self.ticker(TickerBytesRead).unwrap_or(0)
+    }
+
+    /// The number of uncompressed bytes written by puts, deletes and merges.
+    pub fn bytes_written(&self) -> u64 {
+        self.ticker(TickerBytesWritten).unwrap_or(0)
+    }
+
+    /// The number of bytes read by compactions.
+    pub fn compaction_bytes_read(&self) -> u64 {
+        self.ticker(TickerCompactReadBytes).unwrap_or(0)
+    }
+
+    /// The number of bytes written by compactions.
+    pub fn compaction_bytes_written(&self) -> u64 {
+        self.ticker(TickerCompactWriteBytes).unwrap_or(0)
+    }
+
+    /// The total time writes have been stalled, in microseconds, because of level 0 slowdowns,
+    /// memtable compactions or too many level 0 files.
+    pub fn stall_micros(&self) -> u64 {
+        [TickerStallL0SlowdownMicros,
+         TickerStallMemtableCompactionMicros,
+         TickerStallL0NumFilesMicros].iter()
+                                     .map(|ticker| self.ticker(ticker.clone()).unwrap_or(0))
+                                     .fold(0, |total, micros| total + micros)
+    }
+
+    /// The latency of gets, in microseconds.
+    pub fn get_micros(&self) -> Option<Histogram> {
+        self.histogram(HistogramDbGet)
+    }
+
+    /// The latency of writes, in microseconds.
+    pub fn write_micros(&self) -> Option<Histogram> {
+        self.histogram(HistogramDbWrite)
+    }
+}
+
+/// Parse the `<name> : <value>` pairs of a histogram line, using the provided names for the
+/// percentiles. Returns `None` if the pairs are malformed or a percentile is missing.
+fn parse_histogram(fields: &[&str], p50: &str, p95: &str, p99: &str) -> Option<Histogram> {
+    let mut pairs = Vec::new();
+    for pair in fields.chunks(3) {
+        if pair.len() != 3 || pair[1] != ":" {
+            return None;
+        }
+        pairs.push((pair[0], pair[2]));
+    }
+    let value = |name: &str| pairs.iter().find(|&&(key, _)| key == name).map(|&(_, value)| value);
+    let percentiles = (value(p50).and_then(from_str::<f64>),
+                       value(p95).and_then(from_str::<f64>),
+                       value(p99).and_then(from_str::<f64>));
+    match percentiles {
+        (Some(median), Some(percentile95), Some(percentile99)) => {
+            Some(Histogram { median: median,
+                             percentile95: percentile95,
+                             percentile99: percentile99,
+                             count: value("COUNT").and_then(from_str::<u64>),
+                             sum: value("SUM").and_then(from_str::<u64>) })
+        },
+        _ => None
+    }
+}
+
+/// Re-enables deletion of obsolete database files when dropped. See `Database::pin_files`.
+struct PinnedFiles<'a> {
+    database: &'a Database
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for PinnedFiles<'a> {
+    fn drop(&mut self) {
+        let mut error: *mut i8 = ptr::null_mut();
+        unsafe {
+            rocksdb_enable_file_deletions(self.database.database, 0, &mut error);
+            if error != ptr::null_mut() {
+                error!("Unable to re-enable file deletions: {}",
+                       CString::new(error as *const i8, true).to_string());
+            }
+        }
+    }
+}
+
+/// A table file which is part of the current state of a database.
+#[deriving(Clone, PartialEq, Show)]
+pub struct LiveFile {
+    /// The column family the file belongs to.
+    pub column_family: String,
+    /// The name of the file, relative to the database directory.
+    pub name: String,
+    /// The level of the file in the column family.
+    pub level: uint,
+    /// The size of the file, in bytes.
+    pub size: u64,
+    /// The smallest key in the file.
+    pub smallest_key: Vec<u8>,
+    /// The largest key in the file.
+    pub largest_key: Vec<u8>
+}
+
+/// Compact a range of a column family, blocking until the compaction is complete.
+unsafe fn compact_range(database: *mut rocksdb_t,
+                        column_family: *mut rocksdb_column_family_handle_t,
+                        start: Option<&[u8]>,
+                        end: Option<&[u8]>) {
+    let (start, start_len) = start.map_or((ptr::null(), 0), |s| (s.as_ptr(), s.len()));
+    let (end, end_len) = end.map_or((ptr::null(), 0), |e| (e.as_ptr(), e.len()));
+    rocksdb_compact_range_cf(database,
+                             column_family,
+                             start as *const i8, start_len as u64,
+                             end as *const i8, end_len as u64);
+}
+
+/// A handle to a range compaction running on a background task. Dropping the handle blocks until
+/// the compaction is complete, so the database outlives the compaction.
+pub struct CompactionHandle<'a> {
+    receiver: Receiver<()>,
+    complete: bool,
+    marker: marker::ContravariantLifetime<'a>
+}
+
+impl<'a> CompactionHandle<'a> {
+
+    fn spawn(database: *mut rocksdb_t,
+             column_families: Vec<uint>,
+             start: Option<&[u8]>,
+             end: Option<&[u8]>)
+             -> CompactionHandle<'a> {
+        // Raw pointers can not be sent to another task. The handle keeps the database borrowed
+        // until the compaction is complete, so the pointers remain valid.
+        let database = database as uint;
+        let start = start.map(|start| start.to_vec());
+        let end = end.map(|end| end.to_vec());
+        let (sender, receiver) = channel();
+        spawn(proc() {
+            for &column_family in column_families.iter() {
+                unsafe {
+                    compact_range(database as *mut rocksdb_t,
+                                  column_family as *mut rocksdb_column_family_handle_t,
+                                  start.as_ref().map(|start| start.as_slice()),
+                                  end.as_ref().map(|end| end.as_slice()));
+                }
+            }
+            sender.send(());
+        });
+        CompactionHandle { receiver: receiver,
+                           complete: false,
+                           marker: marker::ContravariantLifetime }
+    }
+
+    /// Returns true if the compaction is complete.
+    pub fn is_complete(&mut self) -> bool {
+        if !self.complete {
+            self.complete = self.receiver.try_recv().is_ok();
+        }
+        self.complete
+    }
+
+    /// Block until the compaction is complete.
+    pub fn wait(mut self) {
+        self.wait_complete();
+    }
+
+    fn wait_complete(&mut self) {
+        if !self.complete {
+            self.receiver.recv();
+            self.complete = true;
+        }
+    }
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for CompactionHandle<'a> {
+    fn drop(&mut self) {
+        self.wait_complete();
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
@@ -323,6 +923,11 @@ pub trait MergeOperator : Sync + Send {
     /// All values passed in will be client-specific values. So if this method returns false, it is
     /// because client specified bad data or there was internal corruption. This will be treated as
     /// an error by the library.
+    ///
+    /// A key which is merged frequently and rarely compacted may accumulate a long chain of
+    /// operands, all of which are passed to a single `full_merge` call, so implementations should
+    /// take time linear in the number of operands. Use
+    /// `ColumnFamilyOptions::set_max_successive_merges` to bound the chain length in the memtable.
     fn full_merge(&self,
                   key: &[u8],
                   existing_val: Option<&[u8]>,
@@ -341,7 +946,10 @@ pub trait MergeOperator : Sync + Send {
     ///
     /// `partial_merge` will be called only when the list of operands are long enough. The minimum
     /// number of operands that will be passed to the function is specified by the
-    /// `ColumnFamilyOptions::min_partial_merge_operands` option.
+    /// `ColumnFamilyOptions::set_min_partial_merge_operands` option.
+    ///
+    /// If the operands can not be combined, return an error. The operands are then kept as they
+    /// are, and passed to a later `full_merge`.
     fn partial_merge(&self,
                      key: &[u8],
                      operands: Operands)
@@ -388,6 +996,140 @@ impl<T: AssociativeMergeOperator> MergeOperator for T {
     }
 }
 
+/// A type which can be stored in a RocksDB database as a value or merge operand.
+pub trait Codec {
+    fn encode(&self) -> io::IoResult<Vec<u8>>;
+    fn decode(bytes: &[u8]) -> io::IoResult<Self>;
+}
+
+impl Codec for Vec<u8> {
+    fn encode(&self) -> io::IoResult<Vec<u8>> {
+        Ok(self.clone())
+    }
+
+    fn decode(bytes: &[u8]) -> io::IoResult<Vec<u8>> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Codec for String {
+    fn encode(&self) -> io::IoResult<Vec<u8>> {
+        Ok(self.as_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> io::IoResult<String> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| {
+            io::IoError { kind: io::InvalidInput, desc: "value is not UTF-8", detail: None }
+        })
+    }
+}
+
+/// Encoded as 8 big-endian bytes.
+impl Codec for u64 {
+    fn encode(&self) -> io::IoResult<Vec<u8>> {
+        let mut writer = io::MemWriter::with_capacity(8);
+        try!(writer.write_be_u64(*self));
+        Ok(writer.unwrap())
+    }
+
+    fn decode(bytes: &[u8]) -> io::IoResult<u64> {
+        if bytes.len() != 8 {
+            return Err(io::IoError { kind: io::InvalidInput,
+                                     desc: "expected an 8 byte integer",
+                                     detail: Some(format!("found {} bytes", bytes.len())) });
+        }
+        io::BufReader::new(bytes).read_be_u64()
+    }
+}
+
+/// Encoded as 8 big-endian bytes.
+impl Codec for i64 {
+    fn encode(&self) -> io::IoResult<Vec<u8>> {
+        (*self as u64).encode()
+    }
+
+    fn decode(bytes: &[u8]) -> io::IoResult<i64> {
+        let value: u64 = try!(Codec::decode(bytes));
+        Ok(value as i64)
+    }
+}
+
+/// A merge operator over decoded values and operands.
+///
+/// Values of type `V` and operands of type `Op` are decoded before being passed to the merge
+/// functions, and the results are encoded before being handed back to RocksDB. A value or operand
+/// which fails to decode fails the merge. Install a typed merge operator with
+/// `ColumnFamilyOptions::set_typed_merge_operator`.
+///
+/// See `MergeOperator` for the semantics of `full_merge` and `partial_merge`.
+pub trait TypedMergeOperator<V: Codec, Op: Codec> : Sync + Send {
+
+    fn full_merge(&self, key: &[u8], existing_val: Option<V>, operands: Vec<Op>) -> io::IoResult<V>;
+
+    fn partial_merge(&self, key: &[u8], operands: Vec<Op>) -> io::IoResult<Op>;
+}
+
+/// Adapts a `TypedMergeOperator` to the `MergeOperator` interface.
+pub struct TypedMergeOperatorAdapter<V, Op, M> {
+    merge_operator: M,
+    marker: marker::CovariantType<(V, Op)>
+}
+
+impl<V: Codec + Sync + Send,
+     Op: Codec + Sync + Send,
+     M: TypedMergeOperator<V, Op>> TypedMergeOperatorAdapter<V, Op, M> {
+
+    pub fn new(merge_operator: M) -> TypedMergeOperatorAdapter<V, Op, M> {
+        TypedMergeOperatorAdapter { merge_operator: merge_operator,
+                                    marker: marker::CovariantType }
+    }
+
+    fn decode_operands(operands: Operands) -> io::IoResult<Vec<Op>> {
+        let mut decoded = Vec::with_capacity(operands.size_hint().val0());
+        for operand in operands {
+            decoded.push(try!(Codec::decode(operand).map_err(|error| {
+                decode_error("operand", error)
+            })));
+        }
+        Ok(decoded)
+    }
+}
+
+impl<V: Codec + Sync + Send,
+     Op: Codec + Sync + Send,
+     M: TypedMergeOperator<V, Op>> MergeOperator for TypedMergeOperatorAdapter<V, Op, M> {
+
+    fn full_merge(&self,
+                  key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        let existing_val: Option<V> = match existing_val {
+            Some(val) => {
+                Some(try!(Codec::decode(val).map_err(|error| decode_error("value", error))))
+            },
+            None => None
+        };
+        let operands = try!(TypedMergeOperatorAdapter::<V, Op, M>::decode_operands(operands));
+        try!(self.merge_operator.full_merge(key, existing_val, operands)).encode()
+    }
+
+    fn partial_merge(&self,
+                     key: &[u8],
+                     operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        let operands = try!(TypedMergeOperatorAdapter::<V, Op, M>::decode_operands(operands));
+        try!(self.merge_operator.partial_merge(key, operands)).encode()
+    }
+}
+
+/// Wrap a decoding failure as a merge error.
+fn decode_error(what: &str, error: io::IoError) -> io::IoError {
+    io::IoError { kind: io::InvalidInput,
+                  desc: "merge failed to decode input",
+                  detail: Some(format!("unable to decode {}: {}", what, error)) }
+}
+
 pub struct Operands<'a> {
     operands: slice::Items<'a, *const u8>,
     lens: slice::Items<'a, u64>,
@@ -524,16 +1266,28 @@ extern fn partial_merge_callback(state: *mut c_void,
             let operands = Operands::new(operands as *const *const u8, operand_lens, num_operands as uint);
             let state: &mut MergeOperatorState = &mut *(state as *mut MergeOperatorState);
 
-            // The RocksDB C API does not correctly handle merge operator failures, so it is better
-            // to catch them here and explicitly unwind the stack. If the failure is propogated
-            // through the C API, a segfault occurs.
-            let mut val = (*state.merge_operator).partial_merge(key, operands).unwrap();
-            val.shrink_to_fit();
-            let ptr = val.as_mut_ptr();
-            *len = val.len() as u64;
-            *success = 1;
-            mem::forget(val);
-            ptr as *mut i8
+            // A failed partial merge is not an error: RocksDB keeps the operands and passes them
+            // to a later full merge.
+            match (*state.merge_operator).partial_merge(key, operands) {
+                Ok(mut val) => {
+                    val.shrink_to_fit();
+                    let ptr = val.as_mut_ptr();
+                    *len = val.len() as u64;
+                    *success = 1;
+                    mem::forget(val);
+                    ptr as *mut i8
+                },
+                Err(error) => {
+                    debug!("partial merge failed: {}", error);
+                    // RocksDB still copies and deletes the result, so return an empty one.
+                    let mut val: Vec<u8> = Vec::new();
+                    let ptr = val.as_mut_ptr();
+                    *len = 0;
+                    *success = 0;
+                    mem::forget(val);
+                    ptr as *mut i8
+                }
+            }
         })
     }
 }
@@ -683,139 +1437,2677 @@ impl WriteBatch {
 ////// Slice Transform
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// A transformation which extracts a prefix from keys. A column family with a prefix extractor
+/// can use hash-based memtable representations, prefix bloom filters and the plain table format.
+///
+/// The transformation must be consistent with the key comparator: keys which share a prefix
+/// must be adjacent in key order.
+pub trait SliceTransform : Sync + Send {
 
+    /// Extract the prefix of a key. Only called for keys which are in the domain of the
+    /// transformation, and the result must be a prefix of `key`. The process is aborted if it is
+    /// not, since the error can not be reported to RocksDB.
+    fn transform<'a>(&self, key: &'a [u8]) -> &'a [u8];
 
-///////////////////////////////////////////////////////////////////////////////////////////////////
-////// Options
-///////////////////////////////////////////////////////////////////////////////////////////////////
+    /// Returns true if a prefix can be extracted from the key.
+    fn in_domain(&self, key: &[u8]) -> bool;
 
-/// Options for opening or creating a RocksDB database.
-pub struct DatabaseOptions {
-  options: *mut rocksdb_options_t
+    /// Returns true if the slice is a possible output of `transform`.
+    fn in_range(&self, prefix: &[u8]) -> bool;
 }
 
-impl Drop for DatabaseOptions {
+/// A slice transform which extracts a fixed length prefix from keys. Keys shorter than the prefix
+/// length are not in the domain of the transformation.
+pub struct FixedPrefixTransform {
+    len: uint
+}
+
+impl FixedPrefixTransform {
+    pub fn new(len: uint) -> FixedPrefixTransform {
+        FixedPrefixTransform { len: len }
+    }
+}
+
+impl SliceTransform for FixedPrefixTransform {
+    fn transform<'a>(&self, key: &'a [u8]) -> &'a [u8] {
+        key.slice_to(self.len)
+    }
+
+    fn in_domain(&self, key: &[u8]) -> bool {
+        key.len() >= self.len
+    }
+
+    fn in_range(&self, prefix: &[u8]) -> bool {
+        prefix.len() == self.len
+    }
+}
+
+struct SliceTransformState {
+    name: CString,
+    slice_transform: Box<SliceTransform>
+}
+
+impl SliceTransformState {
+    fn new(name: &str, slice_transform: Box<SliceTransform>) -> *mut rocksdb_slicetransform_t {
+        let state = box SliceTransformState { name: name.to_c_str(),
+                                              slice_transform: slice_transform };
+        unsafe {
+            rocksdb_slicetransform_create(mem::transmute(state),
+                                          slice_transform_destructor_callback,
+                                          transform_callback,
+                                          in_domain_callback,
+                                          in_range_callback,
+                                          slice_transform_name_callback)
+        }
+    }
+}
+
+/// Callback that rocksdb will execute in order to get the name of the slice transform.
+extern fn slice_transform_name_callback(state: *mut c_void) -> *const i8 {
+     let state: &SliceTransformState = unsafe { &*(state as *mut SliceTransformState) };
+     state.name.as_ptr()
+}
+
+/// Callback that rocksdb will execute to extract the prefix of a key. The prefix points into the
+/// key, so rocksdb does not need to free it.
+extern fn transform_callback(state: *mut c_void,
+                             key: *const i8, key_len: u64,
+                             prefix_len: *mut u64)
+                             -> *mut i8 {
+    unsafe {
+        slice::raw::buf_as_slice(key as *const u8, key_len as uint, |key| {
+            let state: &SliceTransformState = &*(state as *mut SliceTransformState);
+            let prefix = state.slice_transform.transform(key);
+            // Unwinding through RocksDB is undefined behaviour, so abort instead of panicking.
+            if prefix.as_ptr() != key.as_ptr() || prefix.len() > key.len() {
+                error!("slice transform {} did not return a prefix of the key",
+                       state.name.as_str().unwrap_or(""));
+                libc::abort();
+            }
+            *prefix_len = prefix.len() as u64;
+            prefix.as_ptr() as *mut i8
+        })
+    }
+}
+
+/// Callback that rocksdb will execute to check whether a key is in the domain of the slice
+/// transform.
+extern fn in_domain_callback(state: *mut c_void, key: *const i8, key_len: u64) -> u8 {
+    unsafe {
+        slice::raw::buf_as_slice(key as *const u8, key_len as uint, |key| {
+            let state: &SliceTransformState = &*(state as *mut SliceTransformState);
+            if state.slice_transform.in_domain(key) { 1 } else { 0 }
+        })
+    }
+}
+
+/// Callback that rocksdb will execute to check whether a slice is in the range of the slice
+/// transform.
+extern fn in_range_callback(state: *mut c_void, prefix: *const i8, prefix_len: u64) -> u8 {
+    unsafe {
+        slice::raw::buf_as_slice(prefix as *const u8, prefix_len as uint, |prefix| {
+            let state: &SliceTransformState = &*(state as *mut SliceTransformState);
+            if state.slice_transform.in_range(prefix) { 1 } else { 0 }
+        })
+    }
+}
+
+/// Callback that rocksdb will execute to destroy the slice transform.
+extern fn slice_transform_destructor_callback(state: *mut c_void) {
+    // Convert back to a box and let destructor reclaim
+    let _: Box<SliceTransformState> = unsafe {mem::transmute(state)};
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+////// Options
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The option settings applied to an options struct, keyed by the option name used in RocksDB
+/// options files. Settings are kept in the order they were applied, so that replaying them in
+/// order reproduces the configuration.
+#[deriving(Clone, PartialEq, Show)]
+struct Settings {
+    settings: Vec<(String, String)>
+}
+
+impl Settings {
+
+    fn new() -> Settings {
+        Settings { settings: Vec::new() }
+    }
+
+    fn record<T: fmt::Show>(&mut self, name: &str, value: T) {
+        self.settings.retain(|&(ref setting, _)| setting.as_slice() != name);
+        self.settings.push((name.to_string(), value.to_string()));
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.settings.iter()
+                     .find(|&&(ref setting, _)| setting.as_slice() == name)
+                     .map(|&(_, ref value)| value.as_slice())
+    }
+}
+
+/// Parse the value of a named option.
+fn parse_option<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    from_str(value).ok_or(format!("invalid value for option {}: {}", name, value))
+}
+
+/// The longest duration, in seconds, which `Duration::seconds` accepts.
+static MAX_DURATION_SECS: i64 = i64::MAX / 1000;
+
+/// Parse the value of an option, failing if it is outside the inclusive range `[min, max]`. Used
+/// for options whose setters panic on values outside the range.
+fn parse_option_in_range<T: FromStr + PartialOrd + fmt::Show>(name: &str,
+                                                                value: &str,
+                                                                min: T,
+                                                                max: T)
+                                                                -> Result<T, String> {
+    let parsed: T = try!(parse_option(name, value));
+    if parsed < min || parsed > max {
+        Err(format!("value for option {} is outside [{}, {}]: {}", name, min, max, value))
+    } else {
+        Ok(parsed)
+    }
+}
+
+/// Join a list of values with `:`, the list separator in RocksDB options files.
+fn join_list<T: fmt::Show>(values: &[T]) -> String {
+    values.iter().map(|value| value.to_string()).collect::<Vec<String>>().connect(":")
+}
+
+/// Split the nested settings of an option value formatted as `{name=value;name=value}`.
+fn parse_nested_settings<'a>(s: &'a str) -> Option<Vec<(&'a str, &'a str)>> {
+    let s = s.trim();
+    if !s.starts_with("{") || !s.ends_with("}") {
+        return None;
+    }
+    let mut settings = Vec::new();
+    for setting in s.slice(1, s.len() - 1).split(';').filter(|s| !s.trim().is_empty()) {
+        match setting.find('=') {
+            Some(idx) => settings.push((setting.slice_to(idx).trim(),
+                                        setting.slice_from(idx + 1).trim())),
+            None => return None
+        }
+    }
+    Some(settings)
+}
+
+/// Options for opening or creating a RocksDB database.
+pub struct DatabaseOptions {
+    options: *mut rocksdb_options_t,
+    settings: Settings
+}
+
+impl Drop for DatabaseOptions {
   fn drop(&mut self) {
     debug!("DatabaseOptions::drop");
     unsafe { rocksdb_options_destroy(self.options) }
   }
 }
 
-impl DatabaseOptions {
+impl DatabaseOptions {
+
+    /// Create a new database options struct for specifying configuration to use when opening or
+    /// creating a database.
+    pub fn new() -> DatabaseOptions {
+        let options = unsafe { rocksdb_options_create() };
+        DatabaseOptions { options: options, settings: Settings::new() }
+    }
+
+    /// By default, RocksDB uses a single background thread for flush and compaction. Calling this
+    /// function will set it up such that `total_threads` number of threads is used. A good value
+    /// for `total_threads` is the number of cores. You should call this function if your system is
+    /// bottlenecked by RocksDB.
+    pub fn increase_parallelism(&mut self, total_threads: i32) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_increase_parallelism(self.options, total_threads) };
+        self.settings.record("increase_parallelism", total_threads);
+        self
+    }
+
+    /// Configure the database for bulk-writing. All data will be flushed to level 0 without
+    /// automatic compaction. It is recommended to manually compact the database before resuming
+    /// reads.
+    pub fn prepare_for_bulkload(&mut self) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_prepare_for_bulk_load(self.options) };
+        self.settings.record("prepare_for_bulkload", true);
+        self
+    }
+
+    /// Collect statistics about the operation of the database, which can be retrieved with
+    /// `Database::statistics`. Collecting statistics adds a small overhead to every operation.
+    pub fn enable_statistics(&mut self) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_enable_statistics(self.options) };
+        self.settings.record("enable_statistics", true);
+        self
+    }
+
+    /// Preset for workloads dominated by point lookups. Keeps table files open and advises the
+    /// operating system that table files are accessed randomly. Combine with
+    /// `ColumnFamilyOptions::preset_point_lookup`.
+    pub fn preset_point_lookup(&mut self) -> &mut DatabaseOptions {
+        self.set_max_open_files(None)
+            .set_advise_random_on_open(true)
+    }
+
+    /// Preset for write-heavy ingest workloads. Uses `background_threads` background threads, as
+    /// `increase_parallelism` does: one in the high priority pool for flushes, and the rest for
+    /// compactions. Files are synced incrementally to avoid large write bursts. Combine with
+    /// `ColumnFamilyOptions::preset_write_heavy`.
+    pub fn preset_write_heavy(&mut self, background_threads: i32) -> &mut DatabaseOptions {
+        self.increase_parallelism(background_threads)
+            .set_bytes_per_sync(1 << 20)
+    }
+
+    /// Preset for range scan and analytics workloads. Advises the operating system that table
+    /// files are read sequentially. Combine with `ColumnFamilyOptions::preset_range_scan`.
+    pub fn preset_range_scan(&mut self) -> &mut DatabaseOptions {
+        self.set_advise_random_on_open(false)
+            .set_access_hint_on_compaction_start(AccessHintSequential)
+    }
+
+    /// Preset for embedded use with a small memory footprint. Limits open files, background work
+    /// and info log retention. Combine with `ColumnFamilyOptions::preset_low_memory`.
+    pub fn preset_low_memory(&mut self) -> &mut DatabaseOptions {
+        self.set_max_open_files(Some(64))
+            .set_table_cache_numshardbits(1)
+            .set_max_background_compactions(1)
+            .set_max_background_flushes(1)
+            .set_max_log_file_size(1 << 20)
+            .set_keep_log_file_num(2)
+            .set_manifest_preallocation_size(64 << 10)
+    }
+
+    /// If true, the implementation will do aggressive checking of the data it is processing and
+    /// will stop early if it detects any errors. This may have unforeseen ramifications: for
+    /// example, a corruption of one database entry may cause a large number of entries to become
+    /// unreadable or for the entire database to become unopenable.
+    ///
+    /// Default: true
+    pub fn set_paranoid_checks(&mut self, paranoid_checks: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_paranoid_checks(self.options, if paranoid_checks { 1 } else { 0 });
+        }
+        self.settings.record("paranoid_checks", paranoid_checks);
+        self
+    }
+
+    /// Number of open files that can be used by the database. You may need to increase this if
+    /// your database has a large working set. `None` means that opened files are always kept open.
+    /// Panics if `max_open_files` is greater than `i32::MAX`.
+    ///
+    /// Default: 5000
+    pub fn set_max_open_files(&mut self, max_open_files: Option<u32>) -> &mut DatabaseOptions {
+        let max_open_files = max_open_files.map_or(-1, |max| c_int_option("max_open_files", max));
+        unsafe { rocksdb_options_set_max_open_files(self.options, max_open_files) };
+        self.settings.record("max_open_files", max_open_files);
+        self
+    }
+
+    /// Maximum number of concurrent background compaction jobs, submitted to the default low
+    /// priority thread pool. Panics if `max_background_compactions` is greater than `i32::MAX`.
+    ///
+    /// Default: 1
+    pub fn set_max_background_compactions(&mut self,
+                                          max_background_compactions: u32)
+                                          -> &mut DatabaseOptions {
+        let max = c_int_option("max_background_compactions", max_background_compactions);
+        unsafe { rocksdb_options_set_max_background_compactions(self.options, max) };
+        self.settings.record("max_background_compactions", max_background_compactions);
+        self
+    }
+
+    /// Maximum number of concurrent background memtable flush jobs, submitted to the high
+    /// priority thread pool.
+    ///
+    /// By default, all background jobs (major compaction and memtable flush) go to the low
+    /// priority pool. If this option is set to a positive number, memtable flush jobs will be
+    /// submitted to the high priority pool. It is important when the same environment is shared
+    /// by multiple databases.
+    ///
+    /// Panics if `max_background_flushes` is greater than `i32::MAX`.
+    ///
+    /// Default: 1
+    pub fn set_max_background_flushes(&mut self,
+                                      max_background_flushes: u32)
+                                      -> &mut DatabaseOptions {
+        let max = c_int_option("max_background_flushes", max_background_flushes);
+        unsafe { rocksdb_options_set_max_background_flushes(self.options, max) };
+        self.settings.record("max_background_flushes", max_background_flushes);
+        self
+    }
+
+    /// The directory of the info log files. By default the info log files are kept in the
+    /// database directory, and their names are prefixed with the database path when a separate
+    /// directory is used.
+    pub fn set_db_log_dir(&mut self, db_log_dir: &Path) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_db_log_dir(self.options, db_log_dir.to_c_str().as_ptr()) };
+        self.settings.record("db_log_dir", db_log_dir.display());
+        self
+    }
+
+    /// The minimum level of messages written to the info log.
+    ///
+    /// Default: `InfoLevel`
+    pub fn set_info_log_level(&mut self, info_log_level: InfoLogLevel) -> &mut DatabaseOptions {
+        self.settings.record("info_log_level", info_log_level.clone());
+        unsafe { rocksdb_options_set_info_log_level(self.options, info_log_level as i32) };
+        self
+    }
+
+    /// Forward the lines of the info log to the `log` crate, as records with the module path
+    /// `rocksdb`. Warnings are forwarded at the warn level, errors at the error level, and all
+    /// other lines at the info level. The info log files are still written.
+    ///
+    /// Default: `false`
+    pub fn set_forward_info_log(&mut self, forward_info_log: bool) -> &mut DatabaseOptions {
+        self.settings.record("forward_info_log", forward_info_log);
+        self
+    }
+
+    /// The directory of the write ahead log files. By default the write ahead log files are kept
+    /// in the database directory. Placing them on a separate device allows the log and the table
+    /// files to be written in parallel.
+    ///
+    /// When destroying the database, all log files in the write ahead log directory and the
+    /// directory itself are deleted.
+    pub fn set_wal_dir(&mut self, wal_dir: &Path) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_wal_dir(self.options, wal_dir.to_c_str().as_ptr()) };
+        self.settings.record("wal_dir", wal_dir.display());
+        self
+    }
+
+    /// Controls how long archived write ahead log files are kept. Obsolete write ahead log files
+    /// are archived instead of deleted if either the time to live or the size limit is non-zero.
+    /// Archived files older than the time to live are deleted, checked at intervals of
+    /// `wal_ttl / 2`. The time to live is rounded up to a whole number of seconds.
+    ///
+    /// Panics if `wal_ttl` is negative.
+    ///
+    /// Default: 0 (no time limit)
+    pub fn set_wal_ttl(&mut self, wal_ttl: Duration) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_WAL_ttl_seconds(self.options, duration_secs(wal_ttl)) };
+        self.settings.record("WAL_ttl_seconds", duration_secs(wal_ttl));
+        self
+    }
+
+    /// Controls the total size of archived write ahead log files, in bytes. When the size limit is
+    /// exceeded, the oldest archived files are deleted until the archive fits within the limit.
+    /// The limit is tracked by RocksDB in megabytes, so `wal_size_limit` is rounded up to a whole
+    /// number of megabytes.
+    ///
+    /// Default: 0 (no size limit)
+    pub fn set_wal_size_limit(&mut self, wal_size_limit: u64) -> &mut DatabaseOptions {
+        let megabytes = (wal_size_limit >> 20) + if wal_size_limit & 0xFFFFF == 0 { 0 } else { 1 };
+        unsafe { rocksdb_options_set_WAL_size_limit_MB(self.options, megabytes) };
+        self.settings.record("WAL_size_limit_MB", megabytes);
+        self
+    }
+
+    /// Maximal size of an info log file, in bytes. If the log file is larger than
+    /// `max_log_file_size`, a new info log file will be created. If `max_log_file_size` is 0, all
+    /// logs will be written to one log file.
+    ///
+    /// Default: 0
+    pub fn set_max_log_file_size(&mut self, max_log_file_size: u64) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_max_log_file_size(self.options, max_log_file_size) };
+        self.settings.record("max_log_file_size", max_log_file_size);
+        self
+    }
+
+    /// Time for the info log file to roll, rounded up to a whole number of seconds. If
+    /// `log_file_time_to_roll` is zero, the info log is not rolled based on its age.
+    ///
+    /// Panics if `log_file_time_to_roll` is negative.
+    ///
+    /// Default: 0 (disabled)
+    pub fn set_log_file_time_to_roll(&mut self,
+                                     log_file_time_to_roll: Duration)
+                                     -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_log_file_time_to_roll(self.options,
+                                                      duration_secs(log_file_time_to_roll))
+        };
+        self.settings.record("log_file_time_to_roll", duration_secs(log_file_time_to_roll));
+        self
+    }
+
+    /// Maximal number of info log files to be kept.
+    ///
+    /// Default: 1000
+    pub fn set_keep_log_file_num(&mut self, keep_log_file_num: u64) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_keep_log_file_num(self.options, keep_log_file_num) };
+        self.settings.record("keep_log_file_num", keep_log_file_num);
+        self
+    }
+
+    /// The manifest file is rolled over on reaching this size, in bytes. The older manifest file
+    /// will be deleted.
+    ///
+    /// Default: unlimited
+    pub fn set_max_manifest_file_size(&mut self,
+                                      max_manifest_file_size: u64)
+                                      -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_max_manifest_file_size(self.options, max_manifest_file_size) };
+        self.settings.record("max_manifest_file_size", max_manifest_file_size);
+        self
+    }
+
+    /// Number of bytes to preallocate (via `fallocate`) the manifest files. Default is 4MB, which
+    /// is reasonable to reduce random I/O as well as prevent overallocation for mounts that
+    /// preallocate large amounts of data (such as xfs's allocsize option).
+    ///
+    /// Default: 4MB
+    pub fn set_manifest_preallocation_size(&mut self,
+                                           manifest_preallocation_size: u64)
+                                           -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_manifest_preallocation_size(self.options,
+                                                            manifest_preallocation_size)
+        };
+        self.settings.record("manifest_preallocation_size", manifest_preallocation_size);
+        self
+    }
+
+    /// Number of shards used for the table cache, as a power of two. Panics if
+    /// `table_cache_numshardbits` is greater than `i32::MAX`.
+    ///
+    /// Default: 4
+    pub fn set_table_cache_numshardbits(&mut self,
+                                        table_cache_numshardbits: u32)
+                                        -> &mut DatabaseOptions {
+        let bits = c_int_option("table_cache_numshardbits", table_cache_numshardbits);
+        unsafe { rocksdb_options_set_table_cache_numshardbits(self.options, bits) };
+        self.settings.record("table_cache_numshardbits", table_cache_numshardbits);
+        self
+    }
+
+    /// During data eviction of the table cache, the number of entries to scan in the LRU list
+    /// before giving up. Panics if `table_cache_remove_scan_count_limit` is greater than
+    /// `i32::MAX`.
+    ///
+    /// Default: 16
+    pub fn set_table_cache_remove_scan_count_limit(&mut self,
+                                                   table_cache_remove_scan_count_limit: u32)
+                                                   -> &mut DatabaseOptions {
+        let limit = c_int_option("table_cache_remove_scan_count_limit",
+                                 table_cache_remove_scan_count_limit);
+        unsafe { rocksdb_options_set_table_cache_remove_scan_count_limit(self.options, limit) };
+        self.settings.record("table_cache_remove_scan_count_limit",
+                             table_cache_remove_scan_count_limit);
+        self
+    }
+
+    /// If true, then every store to stable storage will issue a `fsync`. If false, then every
+    /// store to stable storage will issue a `fdatasync`. This parameter should be set to true
+    /// while storing data to filesystems like ext3 that can lose files after a reboot.
+    ///
+    /// Default: false
+    pub fn set_use_fsync(&mut self, use_fsync: bool) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_use_fsync(self.options, if use_fsync { 1 } else { 0 }) };
+        self.settings.record("use_fsync", use_fsync);
+        self
+    }
+
+    /// If true, then the contents of data files are not synced to stable storage. Their contents
+    /// remain in the OS buffers until the OS decides to flush them. This option is good for bulk
+    /// loading (and is set by `prepare_for_bulkload`) but risks data loss on machine crashes.
+    ///
+    /// Default: false
+    pub fn set_disable_data_sync(&mut self, disable_data_sync: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_disable_data_sync(self.options,
+                                                  if disable_data_sync { 1 } else { 0 });
+        }
+        self.settings.record("disableDataSync", disable_data_sync);
+        self
+    }
+
+    /// Use the operating system buffer cache for reads and writes. If false, data is read and
+    /// written without caching in the operating system.
+    ///
+    /// Default: true
+    pub fn set_allow_os_buffer(&mut self, allow_os_buffer: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_allow_os_buffer(self.options, if allow_os_buffer { 1 } else { 0 });
+        }
+        self.settings.record("allow_os_buffer", allow_os_buffer);
+        self
+    }
+
+    /// Allow the operating system to `mmap` files for reading table files.
+    ///
+    /// Default: false
+    pub fn set_allow_mmap_reads(&mut self, allow_mmap_reads: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_allow_mmap_reads(self.options,
+                                                 if allow_mmap_reads { 1 } else { 0 });
+        }
+        self.settings.record("allow_mmap_reads", allow_mmap_reads);
+        self
+    }
+
+    /// Allow the operating system to `mmap` files for writing.
+    ///
+    /// Default: false
+    pub fn set_allow_mmap_writes(&mut self, allow_mmap_writes: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_allow_mmap_writes(self.options,
+                                                  if allow_mmap_writes { 1 } else { 0 });
+        }
+        self.settings.record("allow_mmap_writes", allow_mmap_writes);
+        self
+    }
+
+    /// Disable child processes from inheriting open files.
+    ///
+    /// Default: true
+    pub fn set_is_fd_close_on_exec(&mut self, is_fd_close_on_exec: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_is_fd_close_on_exec(self.options,
+                                                    if is_fd_close_on_exec { 1 } else { 0 });
+        }
+        self.settings.record("is_fd_close_on_exec", is_fd_close_on_exec);
+        self
+    }
+
+    /// Skip log corruption errors on recovery. If true, corrupted records at the end of the write
+    /// ahead log are ignored instead of failing the open.
+    ///
+    /// Default: false
+    pub fn set_skip_log_error_on_recovery(&mut self,
+                                          skip_log_error_on_recovery: bool)
+                                          -> &mut DatabaseOptions {
+        let skip = if skip_log_error_on_recovery { 1 } else { 0 };
+        unsafe { rocksdb_options_set_skip_log_error_on_recovery(self.options, skip) };
+        self.settings.record("skip_log_error_on_recovery", skip_log_error_on_recovery);
+        self
+    }
+
+    /// Dump statistics to the info log at this interval, rounded up to a whole number of seconds.
+    /// A zero period disables the dumps.
+    ///
+    /// Panics if `stats_dump_period` is negative or longer than `u32::MAX` seconds.
+    ///
+    /// Default: 1 hour
+    pub fn set_stats_dump_period(&mut self, stats_dump_period: Duration) -> &mut DatabaseOptions {
+        let seconds = duration_secs(stats_dump_period);
+        assert!(seconds <= u32::MAX as u64, "stats dump period is too long");
+        unsafe { rocksdb_options_set_stats_dump_period_sec(self.options, seconds as u32) };
+        self.settings.record("stats_dump_period_sec", seconds);
+        self
+    }
+
+    /// If true, the operating system is advised that table file access patterns are random when
+    /// table files are opened.
+    ///
+    /// Default: true
+    pub fn set_advise_random_on_open(&mut self,
+                                     advise_random_on_open: bool)
+                                     -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_advise_random_on_open(self.options,
+                                                      if advise_random_on_open { 1 } else { 0 });
+        }
+        self.settings.record("advise_random_on_open", advise_random_on_open);
+        self
+    }
+
+    /// Specify the file access pattern to advise the operating system of once a compaction is
+    /// started. It will be applied to all input files of the compaction.
+    ///
+    /// Default: `AccessHintNormal`
+    pub fn set_access_hint_on_compaction_start(&mut self,
+                                               access_hint: AccessHint)
+                                               -> &mut DatabaseOptions {
+        self.settings.record("access_hint_on_compaction_start", access_hint.clone());
+        unsafe {
+            rocksdb_options_set_access_hint_on_compaction_start(self.options, access_hint as i32)
+        };
+        self
+    }
+
+    /// Use an adaptive mutex, which spins in the user space before resorting to the kernel. This
+    /// could reduce context switches when the mutex is not heavily contended. However, if the
+    /// mutex is hot, we could end up wasting spin time.
+    ///
+    /// Default: false
+    pub fn set_use_adaptive_mutex(&mut self, use_adaptive_mutex: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_use_adaptive_mutex(self.options,
+                                                   if use_adaptive_mutex { 1 } else { 0 });
+        }
+        self.settings.record("use_adaptive_mutex", use_adaptive_mutex);
+        self
+    }
+
+    /// Allows the operating system to incrementally sync files to disk while they are being
+    /// written, asynchronously, in the background. Issue one request for every `bytes_per_sync`
+    /// written. 0 turns it off.
+    ///
+    /// Default: 0
+    pub fn set_bytes_per_sync(&mut self, bytes_per_sync: u64) -> &mut DatabaseOptions {
+        unsafe { rocksdb_options_set_bytes_per_sync(self.options, bytes_per_sync) };
+        self.settings.record("bytes_per_sync", bytes_per_sync);
+        self
+    }
+
+    /// The periodicity with which obsolete files get deleted. Files which go out of scope through
+    /// compaction are always deleted, so this only affects files left behind by other means.
+    ///
+    /// Panics if `delete_obsolete_files_period` is negative.
+    ///
+    /// Default: 6 hours
+    pub fn set_delete_obsolete_files_period(&mut self,
+                                            delete_obsolete_files_period: Duration)
+                                            -> &mut DatabaseOptions {
+        let micros = delete_obsolete_files_period.num_microseconds().expect("period is too long");
+        assert!(micros >= 0, "delete obsolete files period must not be negative");
+        unsafe {
+            rocksdb_options_set_delete_obsolete_files_period_micros(self.options, micros as u64)
+        };
+        self.settings.record("delete_obsolete_files_period_micros", micros);
+        self
+    }
+
+    /// Allow RocksDB to use thread local storage.
+    ///
+    /// Default: true
+    pub fn set_allow_thread_local(&mut self, allow_thread_local: bool) -> &mut DatabaseOptions {
+        unsafe {
+            rocksdb_options_set_allow_thread_local(self.options,
+                                                   if allow_thread_local { 1 } else { 0 });
+        }
+        self.settings.record("allow_thread_local", allow_thread_local);
+        self
+    }
+
+    /// Get the value of an option which has been set, formatted as in a RocksDB options file.
+    /// Options are named as in RocksDB options files, for example `max_open_files` or
+    /// `WAL_ttl_seconds`. Returns `None` if the option has not been set.
+    ///
+    /// Options configured implicitly by `increase_parallelism` or `prepare_for_bulkload` are not
+    /// reported individually; the helpers themselves are reported under their own names.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.settings.get(name)
+    }
+
+    /// Get the name and value of every option which has been set, in the order they were set.
+    pub fn settings(&self) -> &[(String, String)] {
+        self.settings.settings.as_slice()
+    }
+
+    /// Set an option by name from its text value, as it would appear in a RocksDB options file.
+    /// See `get` for the option names.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<&mut DatabaseOptions, String> {
+        match name {
+            "increase_parallelism" => {
+                self.increase_parallelism(try!(parse_option(name, value)));
+            },
+            "prepare_for_bulkload" => {
+                if try!(parse_option(name, value)) { self.prepare_for_bulkload(); }
+            },
+            "enable_statistics" => {
+                if try!(parse_option(name, value)) { self.enable_statistics(); }
+            },
+            "paranoid_checks" => { self.set_paranoid_checks(try!(parse_option(name, value))); },
+            "max_open_files" => {
+                let max: i32 = try!(parse_option(name, value));
+                self.set_max_open_files(if max < 0 { None } else { Some(max as u32) });
+            },
+            "max_background_compactions" => {
+                let max = try!(parse_option_in_range(name, value, 0, i32::MAX as u32));
+                self.set_max_background_compactions(max);
+            },
+            "max_background_flushes" => {
+                let max = try!(parse_option_in_range(name, value, 0, i32::MAX as u32));
+                self.set_max_background_flushes(max);
+            },
+            "db_log_dir" => { self.set_db_log_dir(&Path::new(value)); },
+            "info_log_level" => { self.set_info_log_level(try!(parse_option(name, value))); },
+            "forward_info_log" => { self.set_forward_info_log(try!(parse_option(name, value))); },
+            "wal_dir" => { self.set_wal_dir(&Path::new(value)); },
+            "WAL_ttl_seconds" => {
+                let seconds = try!(parse_option_in_range(name, value, 0, MAX_DURATION_SECS));
+                self.set_wal_ttl(Duration::seconds(seconds));
+            },
+            "WAL_size_limit_MB" => {
+                let megabytes = try!(parse_option_in_range(name, value, 0, u64::MAX >> 20));
+                self.set_wal_size_limit(megabytes << 20);
+            },
+            "max_log_file_size" => { self.set_max_log_file_size(try!(parse_option(name, value))); },
+            "log_file_time_to_roll" => {
+                let seconds = try!(parse_option_in_range(name, value, 0, MAX_DURATION_SECS));
+                self.set_log_file_time_to_roll(Duration::seconds(seconds));
+            },
+            "keep_log_file_num" => { self.set_keep_log_file_num(try!(parse_option(name, value))); },
+            "max_manifest_file_size" => {
+                self.set_max_manifest_file_size(try!(parse_option(name, value)));
+            },
+            "manifest_preallocation_size" => {
+                self.set_manifest_preallocation_size(try!(parse_option(name, value)));
+            },
+            "table_cache_numshardbits" => {
+                let bits = try!(parse_option_in_range(name, value, 0, i32::MAX as u32));
+                self.set_table_cache_numshardbits(bits);
+            },
+            "table_cache_remove_scan_count_limit" => {
+                let limit = try!(parse_option_in_range(name, value, 0, i32::MAX as u32));
+                self.set_table_cache_remove_scan_count_limit(limit);
+            },
+            "use_fsync" => { self.set_use_fsync(try!(parse_option(name, value))); },
+            "disableDataSync" => { self.set_disable_data_sync(try!(parse_option(name, value))); },
+            "allow_os_buffer" => { self.set_allow_os_buffer(try!(parse_option(name, value))); },
+            "allow_mmap_reads" => { self.set_allow_mmap_reads(try!(parse_option(name, value))); },
+            "allow_mmap_writes" => { self.set_allow_mmap_writes(try!(parse_option(name, value))); },
+            "is_fd_close_on_exec" => {
+                self.set_is_fd_close_on_exec(try!(parse_option(name, value)));
+            },
+            "skip_log_error_on_recovery" => {
+                self.set_skip_log_error_on_recovery(try!(parse_option(name, value)));
+            },
+            "stats_dump_period_sec" => {
+                let seconds = try!(parse_option_in_range(name, value, 0, u32::MAX as i64));
+                self.set_stats_dump_period(Duration::seconds(seconds));
+            },
+            "advise_random_on_open" => {
+                self.set_advise_random_on_open(try!(parse_option(name, value)));
+            },
+            "access_hint_on_compaction_start" => {
+                self.set_access_hint_on_compaction_start(try!(parse_option(name, value)));
+            },
+            "use_adaptive_mutex" => {
+                self.set_use_adaptive_mutex(try!(parse_option(name, value)));
+            },
+            "bytes_per_sync" => { self.set_bytes_per_sync(try!(parse_option(name, value))); },
+            "delete_obsolete_files_period_micros" => {
+                let micros = try!(parse_option_in_range(name, value, 0, i64::MAX));
+                self.set_delete_obsolete_files_period(Duration::microseconds(micros));
+            },
+            "allow_thread_local" => {
+                self.set_allow_thread_local(try!(parse_option(name, value)));
+            },
+            _ => return Err(format!("unknown database option: {}", name))
+        }
+        Ok(self)
+    }
+
+    /// Get the raw `rocksdb_options_t` struct.
+    fn options(&self) -> *const rocksdb_options_t {
+        self.options as *const rocksdb_options_t
+    }
+
+    /// Mutably get the raw `rocksdb_options_t` struct.
+    fn options_mut(&self) -> *mut rocksdb_options_t {
+        self.options
+    }
+}
+
+/// File access pattern advice given to the operating system.
+#[deriving(Clone, PartialEq, Eq)]
+pub enum AccessHint {
+    AccessHintNone = 0,
+    AccessHintNormal = 1,
+    AccessHintSequential = 2,
+    AccessHintWillNeed = 3
+}
+
+/// Formats the access hint with the name used in RocksDB options files.
+impl fmt::Show for AccessHint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            AccessHintNone => "NONE",
+            AccessHintNormal => "NORMAL",
+            AccessHintSequential => "SEQUENTIAL",
+            AccessHintWillNeed => "WILLNEED"
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for AccessHint {
+    fn from_str(s: &str) -> Option<AccessHint> {
+        match s {
+            "NONE" => Some(AccessHintNone),
+            "NORMAL" => Some(AccessHintNormal),
+            "SEQUENTIAL" => Some(AccessHintSequential),
+            "WILLNEED" => Some(AccessHintWillNeed),
+            _ => None
+        }
+    }
+}
+
+/// The severity of a message written to the info log.
+#[deriving(Clone, PartialEq, Eq)]
+pub enum InfoLogLevel {
+    DebugLevel = 0,
+    InfoLevel = 1,
+    WarnLevel = 2,
+    ErrorLevel = 3,
+    FatalLevel = 4
+}
+
+/// Formats the info log level with the name used in RocksDB options files.
+impl fmt::Show for InfoLogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            DebugLevel => "DEBUG_LEVEL",
+            InfoLevel => "INFO_LEVEL",
+            WarnLevel => "WARN_LEVEL",
+            ErrorLevel => "ERROR_LEVEL",
+            FatalLevel => "FATAL_LEVEL"
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for InfoLogLevel {
+    fn from_str(s: &str) -> Option<InfoLogLevel> {
+        match s {
+            "DEBUG_LEVEL" => Some(DebugLevel),
+            "INFO_LEVEL" => Some(InfoLevel),
+            "WARN_LEVEL" => Some(WarnLevel),
+            "ERROR_LEVEL" => Some(ErrorLevel),
+            "FATAL_LEVEL" => Some(FatalLevel),
+            _ => None
+        }
+    }
+}
+
+/// A ticker collected by a database with statistics enabled. See `Statistics::ticker`.
+#[deriving(Clone, PartialEq, Eq)]
+pub enum Ticker {
+    /// Block cache misses.
+    TickerBlockCacheMiss,
+    /// Block cache hits.
+    TickerBlockCacheHit,
+    /// Blocks added to the block cache.
+    TickerBlockCacheAdd,
+    /// Block cache misses for index blocks.
+    TickerBlockCacheIndexMiss,
+    /// Block cache hits for index blocks.
+    TickerBlockCacheIndexHit,
+    /// Block cache misses for filter blocks.
+    TickerBlockCacheFilterMiss,
+    /// Block cache hits for filter blocks.
+    TickerBlockCacheFilterHit,
+    /// Block cache misses for data blocks.
+    TickerBlockCacheDataMiss,
+    /// Block cache hits for data blocks.
+    TickerBlockCacheDataHit,
+    /// Table file reads avoided by bloom filters.
+    TickerBloomFilterUseful,
+    /// Gets answered by a memtable.
+    TickerMemtableHit,
+    /// Gets not answered by a memtable.
+    TickerMemtableMiss,
+    /// Keys dropped by compactions because a newer entry exists.
+    TickerCompactionKeyDropNewerEntry,
+    /// Deleted keys dropped by compactions.
+    TickerCompactionKeyDropObsolete,
+    /// Keys dropped by compaction filters.
+    TickerCompactionKeyDropUser,
+    /// Keys written by puts, deletes and merges.
+    TickerNumberKeysWritten,
+    /// Keys read by gets.
+    TickerNumberKeysRead,
+    /// Keys updated in place in a memtable.
+    TickerNumberKeysUpdated,
+    /// Uncompressed bytes written by puts, deletes and merges.
+    TickerBytesWritten,
+    /// Uncompressed bytes read by gets.
+    TickerBytesRead,
+    /// Table files closed.
+    TickerNoFileCloses,
+    /// Table files opened.
+    TickerNoFileOpens,
+    /// Errors opening table files.
+    TickerNoFileErrors,
+    /// Microseconds writes were slowed down by level 0 files.
+    TickerStallL0SlowdownMicros,
+    /// Microseconds writes were stalled waiting for memtables to be flushed.
+    TickerStallMemtableCompactionMicros,
+    /// Microseconds writes were stalled by too many level 0 files.
+    TickerStallL0NumFilesMicros,
+    /// Milliseconds writes were delayed by the rate limit.
+    TickerRateLimitDelayMillis,
+    /// Open iterators.
+    TickerNoIterators,
+    /// Multi-gets.
+    TickerNumberMultigetCalls,
+    /// Keys read by multi-gets.
+    TickerNumberMultigetKeysRead,
+    /// Bytes read by multi-gets.
+    TickerNumberMultigetBytesRead,
+    /// Deletes skipped because the key did not exist.
+    TickerNumberFilteredDeletes,
+    /// Merges which the merge operator failed.
+    TickerNumberMergeFailures,
+    /// The last sequence number.
+    TickerSequenceNumber,
+    /// Prefix bloom filter checks.
+    TickerBloomFilterPrefixChecked,
+    /// Reads avoided by prefix bloom filters.
+    TickerBloomFilterPrefixUseful,
+    /// Reseeks made by iterators skipping over entries for a key.
+    TickerNumberOfReseeksInIteration,
+    /// Calls to get the updates since a sequence number.
+    TickerGetUpdatesSinceCalls,
+    /// Compressed block cache misses.
+    TickerBlockCacheCompressedMiss,
+    /// Compressed block cache hits.
+    TickerBlockCacheCompressedHit,
+    /// Syncs of the write ahead log.
+    TickerWalFileSynced,
+    /// Bytes written to the write ahead log.
+    TickerWalFileBytes,
+    /// Writes performed by the writing thread.
+    TickerWriteDoneBySelf,
+    /// Writes performed by another thread, in a batch.
+    TickerWriteDoneByOther,
+    /// Writes which were written to the write ahead log.
+    TickerWriteWithWal,
+    /// Bytes read by compactions.
+    TickerCompactReadBytes,
+    /// Bytes written by compactions.
+    TickerCompactWriteBytes
+}
+
+/// Formats the ticker with its RocksDB name, for example `rocksdb.block.cache.hit`.
+impl fmt::Show for Ticker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TickerBlockCacheMiss => "rocksdb.block.cache.miss",
+            TickerBlockCacheHit => "rocksdb.block.cache.hit",
+            TickerBlockCacheAdd => "rocksdb.block.cache.add",
+            TickerBlockCacheIndexMiss => "rocksdb.block.cache.index.miss",
+            TickerBlockCacheIndexHit => "rocksdb.block.cache.index.hit",
+            TickerBlockCacheFilterMiss => "rocksdb.block.cache.filter.miss",
+            TickerBlockCacheFilterHit => "rocksdb.block.cache.filter.hit",
+            TickerBlockCacheDataMiss => "rocksdb.block.cache.data.miss",
+            TickerBlockCacheDataHit => "rocksdb.block.cache.data.hit",
+            TickerBloomFilterUseful => "rocksdb.bloom.filter.useful",
+            TickerMemtableHit => "rocksdb.memtable.hit",
+            TickerMemtableMiss => "rocksdb.memtable.miss",
+            TickerCompactionKeyDropNewerEntry => "rocksdb.compaction.key.drop.new",
+            TickerCompactionKeyDropObsolete => "rocksdb.compaction.key.drop.obsolete",
+            TickerCompactionKeyDropUser => "rocksdb.compaction.key.drop.user",
+            TickerNumberKeysWritten => "rocksdb.number.keys.written",
+            TickerNumberKeysRead => "rocksdb.number.keys.read",
+            TickerNumberKeysUpdated => "rocksdb.number.keys.updated",
+            TickerBytesWritten => "rocksdb.bytes.written",
+            TickerBytesRead => "rocksdb.bytes.read",
+            TickerNoFileCloses => "rocksdb.no.file.closes",
+            TickerNoFileOpens => "rocksdb.no.file.opens",
+            TickerNoFileErrors => "rocksdb.no.file.errors",
+            TickerStallL0SlowdownMicros => "rocksdb.l0.slowdown.micros",
+            TickerStallMemtableCompactionMicros => "rocksdb.memtable.compaction.micros",
+            TickerStallL0NumFilesMicros => "rocksdb.l0.num.files.stall.micros",
+            TickerRateLimitDelayMillis => "rocksdb.rate.limit.delay.millis",
+            TickerNoIterators => "rocksdb.num.iterators",
+            TickerNumberMultigetCalls => "rocksdb.number.multiget.get",
+            TickerNumberMultigetKeysRead => "rocksdb.number.multiget.keys.read",
+            TickerNumberMultigetBytesRead => "rocksdb.number.multiget.bytes.read",
+            TickerNumberFilteredDeletes => "rocksdb.number.deletes.filtered",
+            TickerNumberMergeFailures => "rocksdb.number.merge.failures",
+            TickerSequenceNumber => "rocksdb.sequence.number",
+            TickerBloomFilterPrefixChecked => "rocksdb.bloom.filter.prefix.checked",
+            TickerBloomFilterPrefixUseful => "rocksdb.bloom.filter.prefix.useful",
+            TickerNumberOfReseeksInIteration => "rocksdb.number.reseeks.iteration",
+            TickerGetUpdatesSinceCalls => "rocksdb.getupdatessince.calls",
+            TickerBlockCacheCompressedMiss => "rocksdb.block.cachecompressed.miss",
+            TickerBlockCacheCompressedHit => "rocksdb.block.cachecompressed.hit",
+            TickerWalFileSynced => "rocksdb.wal.synced",
+            TickerWalFileBytes => "rocksdb.wal.bytes",
+            TickerWriteDoneBySelf => "rocksdb.write.self",
+            TickerWriteDoneByOther => "rocksdb.write.other",
+            TickerWriteWithWal => "rocksdb.write.wal",
+            TickerCompactReadBytes => "rocksdb.compact.read.bytes",
+            TickerCompactWriteBytes => "rocksdb.compact.write.bytes"
+        };
+        f.pad(name)
+    }
+}
+
+/// A histogram collected by a database with statistics enabled. See `Statistics::histogram`.
+#[deriving(Clone, PartialEq, Eq)]
+pub enum HistogramType {
+    /// Microseconds taken by gets.
+    HistogramDbGet,
+    /// Microseconds taken by writes.
+    HistogramDbWrite,
+    /// Microseconds taken by compactions.
+    HistogramCompactionTime,
+    /// Microseconds taken to sync table files written by flushes.
+    HistogramTableSyncMicros,
+    /// Microseconds taken to sync table files written by compactions.
+    HistogramCompactionOutfileSyncMicros,
+    /// Microseconds taken to sync the write ahead log.
+    HistogramWalFileSyncMicros,
+    /// Microseconds taken to sync the MANIFEST.
+    HistogramManifestFileSyncMicros,
+    /// Microseconds spent on IO opening table files.
+    HistogramTableOpenIoMicros,
+    /// Microseconds taken by multi-gets.
+    HistogramDbMultiget,
+    /// Microseconds taken to read blocks during compactions.
+    HistogramReadBlockCompactionMicros,
+    /// Microseconds taken to read blocks during gets.
+    HistogramReadBlockGetMicros,
+    /// Microseconds taken to write blocks.
+    HistogramWriteRawBlockMicros,
+    /// Writes slowed down by level 0 files.
+    HistogramStallL0SlowdownCount,
+    /// Writes stalled waiting for memtables to be flushed.
+    HistogramStallMemtableCompactionCount,
+    /// Writes stalled by too many level 0 files.
+    HistogramStallL0NumFilesCount,
+    /// Writes delayed by the hard rate limit.
+    HistogramHardRateLimitDelayCount,
+    /// Writes delayed by the soft rate limit.
+    HistogramSoftRateLimitDelayCount,
+    /// Input files of each compaction.
+    HistogramNumFilesInSingleCompaction
+}
+
+/// Formats the histogram type with its RocksDB name, for example `rocksdb.db.get.micros`.
+impl fmt::Show for HistogramType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            HistogramDbGet => "rocksdb.db.get.micros",
+            HistogramDbWrite => "rocksdb.db.write.micros",
+            HistogramCompactionTime => "rocksdb.compaction.times.micros",
+            HistogramTableSyncMicros => "rocksdb.table.sync.micros",
+            HistogramCompactionOutfileSyncMicros => "rocksdb.compaction.outfile.sync.micros",
+            HistogramWalFileSyncMicros => "rocksdb.wal.file.sync.micros",
+            HistogramManifestFileSyncMicros => "rocksdb.manifest.file.sync.micros",
+            HistogramTableOpenIoMicros => "rocksdb.table.open.io.micros",
+            HistogramDbMultiget => "rocksdb.db.multiget.micros",
+            HistogramReadBlockCompactionMicros => "rocksdb.read.block.compaction.micros",
+            HistogramReadBlockGetMicros => "rocksdb.read.block.get.micros",
+            HistogramWriteRawBlockMicros => "rocksdb.write.raw.block.micros",
+            HistogramStallL0SlowdownCount => "rocksdb.l0.slowdown.count",
+            HistogramStallMemtableCompactionCount => "rocksdb.memtable.compaction.count",
+            HistogramStallL0NumFilesCount => "rocksdb.num.files.stall.count",
+            HistogramHardRateLimitDelayCount => "rocksdb.hard.rate.limit.delay.count",
+            HistogramSoftRateLimitDelayCount => "rocksdb.soft.rate.limit.delay.count",
+            HistogramNumFilesInSingleCompaction => "rocksdb.numfiles.in.singlecompaction"
+        };
+        f.pad(name)
+    }
+}
+
+/// Convert a duration to seconds, panicking if the duration is negative. Durations are rounded up
+/// to a whole second, since a zero duration disables the options which take one.
+fn duration_secs(duration: Duration) -> u64 {
+    assert!(duration >= Duration::zero(), "duration must not be negative");
+    let secs = duration.num_seconds();
+    if duration > Duration::seconds(secs) { secs as u64 + 1 } else { secs as u64 }
+}
+
+/// Convert an option value to the `int` which RocksDB stores it in, panicking if the value is
+/// greater than `i32::MAX`.
+fn c_int_option(name: &str, value: u32) -> i32 {
+    assert!(value <= i32::MAX as u32, "{} must not exceed i32::MAX", name);
+    value as i32
+}
+
+/// Options for opening or creating a column family in a RocksDB database.
+pub struct ColumnFamilyOptions {
+    options: *mut rocksdb_options_t,
+    comparator: Option<Comparator>,
+    settings: Settings,
+    /// Whether a memtable representation other than the skip list has been selected.
+    memtable_replaced: bool,
+    /// Whether the skip list representation was selected after another representation, and has
+    /// not since been replaced.
+    skip_list_restored: bool
+}
+
+impl Drop for ColumnFamilyOptions {
+    fn drop(&mut self) {
+        debug!("ColumnFamilyOptions::drop");
+        unsafe { rocksdb_options_destroy(self.options) }
+    }
+}
+
+impl ColumnFamilyOptions {
+
+    /// Create a new column family options struct for specifying configuration to use when opening
+    /// or creating a column family.
+    pub fn new() -> ColumnFamilyOptions {
+        let options = unsafe { rocksdb_options_create() };
+        ColumnFamilyOptions {
+            options: options,
+            comparator: None,
+            settings: Settings::new(),
+            memtable_replaced: false,
+            skip_list_restored: false
+        }
+    }
+
+    /// Configure the column family to use level-style compaction with a memtable of size
+    /// `memtable_size`.
+    pub fn optimize_level_style_compaction(&mut self,
+                                           memtable_size: u64)
+                                           -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_optimize_level_style_compaction(self.options, memtable_size) };
+        self.settings.record("optimize_level_style_compaction", memtable_size);
+        self
+    }
+
+    /// Configure the column family to use universal-style compaction with a memtable of size
+    /// `memtable_size`.
+    pub fn optimize_universal_style_compaction(&mut self,
+                                               memtable_size: u64)
+                                               -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_optimize_universal_style_compaction(self.options, memtable_size) };
+        self.settings.record("optimize_universal_style_compaction", memtable_size);
+        self
+    }
+
+    /// Configure the column family for point lookups: a hash indexed block-based table with a
+    /// bloom filter and a block cache of `block_cache_size` bytes (rounded up to a whole number of
+    /// megabytes), along with a hash linked-list memtable.
+    ///
+    /// Range scans over the column family are less efficient with this configuration.
+    pub fn optimize_for_point_lookup(&mut self, block_cache_size: u64) -> &mut ColumnFamilyOptions {
+        let megabytes = (block_cache_size + (1 << 20) - 1) >> 20;
+        unsafe { rocksdb_options_optimize_for_point_lookup(self.options, megabytes) };
+        self.memtable_replaced = true;
+        self.skip_list_restored = false;
+        self.settings.record("optimize_for_point_lookup", block_cache_size);
+        self
+    }
+
+    /// Use the block-based table format with the provided options. The block-based table format
+    /// is the default table format.
+    pub fn set_block_based_table_options(&mut self,
+                                         table_options: &BlockBasedTableOptions)
+                                         -> &mut ColumnFamilyOptions {
+        unsafe {
+            let raw_table_options = table_options.create();
+            rocksdb_options_set_block_based_table_factory(self.options, raw_table_options);
+            rocksdb_block_based_options_destroy(raw_table_options);
+        }
+        self.settings.record("block_based_table_factory", table_options);
+        self
+    }
+
+    /// Use the plain table format with the provided options. The plain table format is optimized
+    /// for low-latency lookups of data held in memory, such as on tmpfs, and requires a prefix
+    /// extractor (see `set_prefix_extractor`) and memory mapped reads (see
+    /// `DatabaseOptions::set_allow_mmap_reads`). Opening a column family which uses the plain table
+    /// format without them fails.
+    pub fn set_plain_table_options(&mut self,
+                                   table_options: &PlainTableOptions)
+                                   -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_plain_table_factory(self.options,
+                                                    table_options.user_key_len,
+                                                    table_options.bloom_bits_per_key,
+                                                    table_options.hash_table_ratio,
+                                                    table_options.index_sparseness)
+        };
+        self.settings.record("plain_table_factory", table_options);
+        self
+    }
+
+    /// Preset for workloads dominated by point lookups. Uses level-style compaction together with
+    /// `optimize_for_point_lookup`.
+    pub fn preset_point_lookup(&mut self, block_cache_size: u64) -> &mut ColumnFamilyOptions {
+        self.optimize_level_style_compaction(DEFAULT_MEMTABLE_MEMORY_BUDGET)
+            .optimize_for_point_lookup(block_cache_size)
+    }
+
+    /// Preset for write-heavy ingest workloads. Uses universal-style compaction, which has lower
+    /// write amplification than level-style compaction at the cost of higher space amplification,
+    /// with `memtable_memory_budget` bytes of memtables. Level-0 write stalls are pushed back, and
+    /// a bloom filter keeps reads over the larger number of files reasonable.
+    pub fn preset_write_heavy(&mut self, memtable_memory_budget: u64) -> &mut ColumnFamilyOptions {
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options.set_bloom_filter_bits_per_key(10);
+        self.optimize_universal_style_compaction(memtable_memory_budget)
+            .set_level0_slowdown_writes_trigger(40)
+            .set_level0_stop_writes_trigger(56)
+            .set_block_based_table_options(&table_options)
+    }
+
+    /// Preset for range scan and analytics workloads. Uses level-style compaction and a
+    /// block-based table with large blocks and a block cache of `block_cache_size` bytes. Bloom
+    /// filters do not help range scans, so none is configured.
+    pub fn preset_range_scan(&mut self, block_cache_size: u64) -> &mut ColumnFamilyOptions {
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options.set_block_size(64 << 10)
+                     .set_block_cache_size(block_cache_size);
+        self.optimize_level_style_compaction(DEFAULT_MEMTABLE_MEMORY_BUDGET)
+            .set_target_file_size_base(64 << 20)
+            .set_block_based_table_options(&table_options)
+    }
+
+    /// Preset for embedded use with a small memory footprint. Uses level-style compaction with
+    /// small memtables and files, and a small block cache.
+    pub fn preset_low_memory(&mut self) -> &mut ColumnFamilyOptions {
+        let mut table_options = BlockBasedTableOptions::new();
+        table_options.set_block_cache_size(1 << 20)
+                     .set_bloom_filter_bits_per_key(10);
+        self.set_write_buffer_size(1 << 20)
+            .set_max_write_buffer_number(2)
+            .set_min_write_buffer_number_to_merge(1)
+            .set_target_file_size_base(2 << 20)
+            .set_max_bytes_for_level_base(8 << 20)
+            .set_arena_block_size(64 << 10)
+            .set_block_based_table_options(&table_options)
+    }
+
+    /// Comparator used to define the order of keys in the table.
+    /// Default: a comparator that uses lexicographic byte-wise ordering
+    ///
+    /// REQUIRES: The client must ensure that the comparator supplied
+    /// here has the same name and orders keys *exactly* the same as the
+    /// comparator provided to previous open calls on the same DB.
+    pub fn set_comparator(&mut self,
+                          name: &str,
+                          compare: |&[u8], &[u8]|: Sync + Send -> Ordering)
+                          -> &mut ColumnFamilyOptions {
+        let comparator = Comparator::new(name, compare);
+        unsafe { rocksdb_options_set_comparator(self.options, comparator.comparator) };
+        self.comparator = Some(comparator);
+        self.settings.record("comparator", name);
+        self
+    }
+
+    /// REQUIRES: The client must provide a merge operator if Merge operation
+    /// needs to be accessed. Calling Merge on a DB without a merge operator
+    /// would result in Status::NotSupported. The client must ensure that the
+    /// merge operator supplied here has the same name and *exactly* the same
+    /// semantics as the merge operator provided to previous open calls on
+    /// the same DB. The only exception is reserved for upgrade, where a DB
+    /// previously without a merge operator is introduced to Merge operation
+    /// for the first time. It's necessary to specify a merge operator when
+    /// openning the DB in this case.
+    /// Default: nullptr
+    pub fn set_merge_operator(&mut self,
+                              name: &str,
+                              merge_operator: Box<MergeOperator>)
+                              -> &mut ColumnFamilyOptions {
+        let merge_operator = MergeOperatorState::new(name, merge_operator);
+        unsafe { rocksdb_options_set_merge_operator(self.options, merge_operator) };
+        self.settings.record("merge_operator", name);
+        self
+    }
+
+    /// Set the prefix extractor of the column family. The prefix extractor is required by
+    /// hash-based memtable representations, and is used by prefix bloom filters. The client must
+    /// ensure that the prefix extractor supplied here has the same semantics as the prefix
+    /// extractor provided to previous open calls on the same DB.
+    ///
+    /// Default: none
+    pub fn set_prefix_extractor(&mut self,
+                                name: &str,
+                                slice_transform: Box<SliceTransform>)
+                                -> &mut ColumnFamilyOptions {
+        let slice_transform = SliceTransformState::new(name, slice_transform);
+        unsafe { rocksdb_options_set_prefix_extractor(self.options, slice_transform) };
+        self.settings.record("prefix_extractor", name);
+        self
+    }
+
+    /// Amount of data to build up in memory (backed by an unsorted log on disk) before converting
+    /// to a sorted on-disk file, in bytes.
+    ///
+    /// Larger values increase performance, especially during bulk loads. Up to
+    /// `max_write_buffer_number` write buffers may be held in memory at the same time, so you may
+    /// wish to adjust this parameter to control memory usage. Also, a larger write buffer will
+    /// result in a longer recovery time the next time the database is opened.
+    ///
+    /// Default: 4MB
+    pub fn set_write_buffer_size(&mut self, write_buffer_size: u64) -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_write_buffer_size(self.options, write_buffer_size) };
+        self.settings.record("write_buffer_size", write_buffer_size);
+        self
+    }
+
+    /// The maximum number of write buffers that are built up in memory. The default is 2, so that
+    /// when 1 write buffer is being flushed to storage, new writes can continue to the other write
+    /// buffer.
+    ///
+    /// Default: 2
+    pub fn set_max_write_buffer_number(&mut self,
+                                       max_write_buffer_number: i32)
+                                       -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_max_write_buffer_number(self.options, max_write_buffer_number)
+        };
+        self.settings.record("max_write_buffer_number", max_write_buffer_number);
+        self
+    }
+
+    /// The minimum number of write buffers that will be merged together before writing to
+    /// storage. If set to 1, then all write buffers are flushed to L0 as individual files and this
+    /// increases read amplification because a get request has to check in all of these files.
+    /// Also, an in-memory merge may result in writing less data to storage if there are duplicate
+    /// records in each of these individual write buffers.
+    ///
+    /// Default: 1
+    pub fn set_min_write_buffer_number_to_merge(&mut self,
+                                                min_write_buffer_number_to_merge: i32)
+                                                -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_min_write_buffer_number_to_merge(self.options,
+                                                                 min_write_buffer_number_to_merge)
+        };
+        self.settings.record("min_write_buffer_number_to_merge", min_write_buffer_number_to_merge);
+        self
+    }
+
+    /// Number of levels for this column family.
+    ///
+    /// Default: 7
+    pub fn set_num_levels(&mut self, num_levels: i32) -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_num_levels(self.options, num_levels) };
+        self.settings.record("num_levels", num_levels);
+        self
+    }
+
+    /// Number of files to trigger level-0 compaction. A value less than 0 means that level-0
+    /// compaction will not be triggered by the number of files at all.
+    ///
+    /// Default: 4
+    pub fn set_level0_file_num_compaction_trigger(&mut self,
+                                                  level0_file_num_compaction_trigger: i32)
+                                                  -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_level0_file_num_compaction_trigger(
+                self.options, level0_file_num_compaction_trigger)
+        };
+        self.settings.record("level0_file_num_compaction_trigger",
+                             level0_file_num_compaction_trigger);
+        self
+    }
+
+    /// Soft limit on the number of level-0 files. Writes are slowed down at this point. A value
+    /// less than 0 means that no write slowdown will be triggered by the number of files in
+    /// level-0.
+    ///
+    /// Default: 20
+    pub fn set_level0_slowdown_writes_trigger(&mut self,
+                                              level0_slowdown_writes_trigger: i32)
+                                              -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_level0_slowdown_writes_trigger(self.options,
+                                                               level0_slowdown_writes_trigger)
+        };
+        self.settings.record("level0_slowdown_writes_trigger", level0_slowdown_writes_trigger);
+        self
+    }
+
+    /// Maximum number of level-0 files. Writes are stopped at this point.
+    ///
+    /// Default: 24
+    pub fn set_level0_stop_writes_trigger(&mut self,
+                                          level0_stop_writes_trigger: i32)
+                                          -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_level0_stop_writes_trigger(self.options, level0_stop_writes_trigger)
+        };
+        self.settings.record("level0_stop_writes_trigger", level0_stop_writes_trigger);
+        self
+    }
+
+    /// Maximum level to which a new compacted memtable is pushed if it does not create overlap.
+    /// We try to push to level 2 to avoid the relatively expensive level 0 to 1 compactions and
+    /// to avoid some expensive manifest file operations. We do not push all the way to the
+    /// largest level since that can generate a lot of wasted disk space if the same key space is
+    /// being repeatedly overwritten.
+    ///
+    /// Default: 2
+    pub fn set_max_mem_compaction_level(&mut self,
+                                        max_mem_compaction_level: i32)
+                                        -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_max_mem_compaction_level(self.options, max_mem_compaction_level)
+        };
+        self.settings.record("max_mem_compaction_level", max_mem_compaction_level);
+        self
+    }
+
+    /// Target file size for compaction, in bytes. The target file size for level `L` is
+    /// `target_file_size_base * (target_file_size_multiplier ^ (L - 1))`. For example, if
+    /// `target_file_size_base` is 2MB and `target_file_size_multiplier` is 10, then each file on
+    /// level 1 will be 2MB, each file on level 2 will be 20MB, and each file on level 3 will be
+    /// 200MB.
+    ///
+    /// Default: 2MB
+    pub fn set_target_file_size_base(&mut self,
+                                     target_file_size_base: u64)
+                                     -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_target_file_size_base(self.options, target_file_size_base) };
+        self.settings.record("target_file_size_base", target_file_size_base);
+        self
+    }
+
+    /// Multiplier of the target file size between levels. See `set_target_file_size_base`.
+    ///
+    /// Default: 1
+    pub fn set_target_file_size_multiplier(&mut self,
+                                           target_file_size_multiplier: u64)
+                                           -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_target_file_size_multiplier(self.options,
+                                                            target_file_size_multiplier)
+        };
+        self.settings.record("target_file_size_multiplier", target_file_size_multiplier);
+        self
+    }
+
+    /// Control the maximum total data size for a level, in bytes. The maximum size of level `L` is
+    /// `max_bytes_for_level_base * (max_bytes_for_level_multiplier ^ (L - 1))`. For example, if
+    /// `max_bytes_for_level_base` is 20MB and `max_bytes_for_level_multiplier` is 10, the total
+    /// data size for level 1 will be 20MB, the total data size for level 2 will be 200MB, and the
+    /// total data size for level 3 will be 2GB.
+    ///
+    /// Default: 10MB
+    pub fn set_max_bytes_for_level_base(&mut self,
+                                        max_bytes_for_level_base: u64)
+                                        -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_max_bytes_for_level_base(self.options, max_bytes_for_level_base)
+        };
+        self.settings.record("max_bytes_for_level_base", max_bytes_for_level_base);
+        self
+    }
+
+    /// Multiplier of the maximum total data size between levels. See
+    /// `set_max_bytes_for_level_base`.
+    ///
+    /// Default: 10
+    pub fn set_max_bytes_for_level_multiplier(&mut self,
+                                              max_bytes_for_level_multiplier: i32)
+                                              -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_max_bytes_for_level_multiplier(self.options,
+                                                               max_bytes_for_level_multiplier)
+        };
+        self.settings.record("max_bytes_for_level_multiplier", max_bytes_for_level_multiplier);
+        self
+    }
+
+    /// Additional per-level multipliers of the maximum total data size, starting with level 1.
+    /// The maximum size of level `L + 1` is `max_bytes(L) * max_bytes_for_level_multiplier *
+    /// max_bytes_for_level_multiplier_additional[L]`.
+    ///
+    /// Default: 1 for every level
+    pub fn set_max_bytes_for_level_multiplier_additional(&mut self,
+                                                         multipliers: &[i32])
+                                                         -> &mut ColumnFamilyOptions {
+        let mut multipliers = multipliers.to_vec();
+        unsafe {
+            rocksdb_options_set_max_bytes_for_level_multiplier_additional(self.options,
+                                                                          multipliers.as_mut_ptr(),
+                                                                          multipliers.len() as u64)
+        };
+        self.settings.record("max_bytes_for_level_multiplier_additional",
+                             join_list(multipliers.as_slice()));
+        self
+    }
+
+    /// Maximum number of bytes in all compacted files, as a multiple of `target_file_size_base`.
+    /// We avoid expanding the lower level file set of a compaction if it would make the total
+    /// compaction cover more than this many bytes.
+    ///
+    /// Default: 25
+    pub fn set_expanded_compaction_factor(&mut self,
+                                          expanded_compaction_factor: i32)
+                                          -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_expanded_compaction_factor(self.options, expanded_compaction_factor)
+        };
+        self.settings.record("expanded_compaction_factor", expanded_compaction_factor);
+        self
+    }
+
+    /// Maximum number of bytes in all source files to be compacted in a single compaction run, as
+    /// a multiple of `target_file_size_base`. We avoid picking too many files in the source level
+    /// so that the total source bytes for the compaction do not exceed this limit.
+    ///
+    /// Default: 1
+    pub fn set_source_compaction_factor(&mut self,
+                                        source_compaction_factor: i32)
+                                        -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_source_compaction_factor(self.options, source_compaction_factor)
+        };
+        self.settings.record("source_compaction_factor", source_compaction_factor);
+        self
+    }
+
+    /// Control the maximum bytes of overlap in the grandparent level (i.e., level + 2) before we
+    /// stop building a single file in a level to level + 1 compaction, as a multiple of
+    /// `target_file_size_base`.
+    ///
+    /// Default: 10
+    pub fn set_max_grandparent_overlap_factor(&mut self,
+                                              max_grandparent_overlap_factor: i32)
+                                              -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_max_grandparent_overlap_factor(self.options,
+                                                               max_grandparent_overlap_factor)
+        };
+        self.settings.record("max_grandparent_overlap_factor", max_grandparent_overlap_factor);
+        self
+    }
+
+    /// Puts are delayed 0-1 ms when any level has a compaction score that exceeds
+    /// `soft_rate_limit`. This is ignored when 0.0.
+    ///
+    /// Default: 0.0 (disabled)
+    pub fn set_soft_rate_limit(&mut self, soft_rate_limit: f64) -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_soft_rate_limit(self.options, soft_rate_limit) };
+        self.settings.record("soft_rate_limit", soft_rate_limit);
+        self
+    }
+
+    /// Puts are delayed 1ms at a time when any level has a compaction score that exceeds
+    /// `hard_rate_limit`. This is ignored when less than 1.0.
+    ///
+    /// Default: 0.0 (disabled)
+    pub fn set_hard_rate_limit(&mut self, hard_rate_limit: f64) -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_hard_rate_limit(self.options, hard_rate_limit) };
+        self.settings.record("hard_rate_limit", hard_rate_limit);
+        self
+    }
+
+    /// Maximum time a put will be stalled when `hard_rate_limit` is enforced. If zero, then there
+    /// is no limit.
+    ///
+    /// Panics if `rate_limit_delay_max` is negative or longer than `u32::MAX` milliseconds.
+    ///
+    /// Default: 1 second
+    pub fn set_rate_limit_delay_max(&mut self,
+                                    rate_limit_delay_max: Duration)
+                                    -> &mut ColumnFamilyOptions {
+        let millis = rate_limit_delay_max.num_milliseconds();
+        assert!(millis >= 0 && millis <= u32::MAX as i64, "rate limit delay is out of range");
+        unsafe {
+            rocksdb_options_set_rate_limit_delay_max_milliseconds(self.options, millis as u32)
+        };
+        self.settings.record("rate_limit_delay_max_milliseconds", millis);
+        self
+    }
+
+    /// Disable automatic compactions. Manual compactions can still be issued on this column
+    /// family.
+    ///
+    /// Default: false
+    pub fn set_disable_auto_compactions(&mut self,
+                                        disable_auto_compactions: bool)
+                                        -> &mut ColumnFamilyOptions {
+        let disable = if disable_auto_compactions { 1 } else { 0 };
+        unsafe { rocksdb_options_set_disable_auto_compactions(self.options, disable) };
+        self.settings.record("disable_auto_compactions", disable_auto_compactions);
+        self
+    }
+
+    /// Set the compaction style of the column family.
+    ///
+    /// Default: `CompactionStyleLevel`
+    pub fn set_compaction_style(&mut self,
+                                compaction_style: CompactionStyle)
+                                -> &mut ColumnFamilyOptions {
+        self.settings.record("compaction_style", compaction_style.clone());
+        unsafe { rocksdb_options_set_compaction_style(self.options, compaction_style as i32) };
+        self
+    }
+
+    /// Set the options used by universal-style compaction. The options only take effect when
+    /// the compaction style is `CompactionStyleUniversal`.
+    pub fn set_universal_compaction_options(&mut self,
+                                            universal_options: &UniversalCompactionOptions)
+                                            -> &mut ColumnFamilyOptions {
+        unsafe {
+            let raw_universal_options = universal_options.create();
+            rocksdb_options_set_universal_compaction_options(self.options,
+                                                             raw_universal_options);
+            rocksdb_universal_compaction_options_destroy(raw_universal_options);
+        }
+        self.settings.record("compaction_options_universal", universal_options);
+        self
+    }
+
+    /// Set the options used by FIFO-style compaction. The options only take effect when the
+    /// compaction style is `CompactionStyleFifo`.
+    pub fn set_fifo_compaction_options(&mut self,
+                                       fifo_options: &FifoCompactionOptions)
+                                       -> &mut ColumnFamilyOptions {
+        unsafe {
+            let raw_fifo_options = fifo_options.create();
+            rocksdb_options_set_fifo_compaction_options(self.options, raw_fifo_options);
+            rocksdb_fifo_compaction_options_destroy(raw_fifo_options);
+        }
+        self.settings.record("compaction_options_fifo", fifo_options);
+        self
+    }
+
+    /// Size of one block in arena memory allocation, in bytes. If zero, a proper value is
+    /// automatically calculated (usually 1/10 of `write_buffer_size`).
+    ///
+    /// Default: 0
+    pub fn set_arena_block_size(&mut self, arena_block_size: u64) -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_arena_block_size(self.options, arena_block_size) };
+        self.settings.record("arena_block_size", arena_block_size);
+        self
+    }
+
+    /// Set the data structure used to hold the memtable. The hash-based representations require a
+    /// prefix extractor (see `set_prefix_extractor`); opening a column family which uses one
+    /// without a prefix extractor fails.
+    ///
+    /// RocksDB can not restore the skip list representation once another representation has been
+    /// selected, either directly or by `optimize_for_point_lookup`, so opening a column family
+    /// which selects `SkipListRep` after another representation fails.
+    ///
+    /// Default: `SkipListRep`
+    pub fn set_memtable_rep(&mut self, memtable_rep: MemtableRep) -> &mut ColumnFamilyOptions {
+        match memtable_rep {
+            SkipListRep => (),
+            VectorRep => unsafe { rocksdb_options_set_memtable_vector_rep(self.options) },
+            HashSkipListRep(bucket_count, skiplist_height, skiplist_branching_factor) => unsafe {
+                rocksdb_options_set_hash_skip_list_rep(self.options,
+                                                       bucket_count,
+                                                       skiplist_height,
+                                                       skiplist_branching_factor)
+            },
+            HashLinkListRep(bucket_count) => unsafe {
+                rocksdb_options_set_hash_link_list_rep(self.options, bucket_count)
+            }
+        }
+        match memtable_rep {
+            SkipListRep => self.skip_list_restored = self.memtable_replaced,
+            _ => {
+                self.memtable_replaced = true;
+                self.skip_list_restored = false;
+            }
+        }
+        self.settings.record("memtable_factory", memtable_rep);
+        self
+    }
+
+    /// Allow values to be updated in place in the memtable when the new value is not larger than
+    /// the existing value. In-place updates reduce memtable growth for frequently overwritten
+    /// keys, but are incompatible with snapshot reads of the overwritten values.
+    ///
+    /// Default: false
+    pub fn set_inplace_update_support(&mut self,
+                                      inplace_update_support: bool)
+                                      -> &mut ColumnFamilyOptions {
+        let support = if inplace_update_support { 1 } else { 0 };
+        unsafe { rocksdb_options_set_inplace_update_support(self.options, support) };
+        self.settings.record("inplace_update_support", inplace_update_support);
+        self
+    }
+
+    /// Number of locks used for in-place updates.
+    ///
+    /// Default: 10000 if in-place updates are supported, otherwise 0
+    pub fn set_inplace_update_num_locks(&mut self,
+                                        inplace_update_num_locks: u64)
+                                        -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_inplace_update_num_locks(self.options, inplace_update_num_locks)
+        };
+        self.settings.record("inplace_update_num_locks", inplace_update_num_locks);
+        self
+    }
+
+    /// If non-zero, and a prefix extractor is set, create a bloom filter of prefixes in the
+    /// memtable with this many bits.
+    ///
+    /// Default: 0
+    pub fn set_memtable_prefix_bloom_bits(&mut self,
+                                          memtable_prefix_bloom_bits: u32)
+                                          -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_memtable_prefix_bloom_bits(self.options, memtable_prefix_bloom_bits)
+        };
+        self.settings.record("memtable_prefix_bloom_bits", memtable_prefix_bloom_bits);
+        self
+    }
+
+    /// Number of hash probes per key in the memtable prefix bloom filter.
+    ///
+    /// Default: 6
+    pub fn set_memtable_prefix_bloom_probes(&mut self,
+                                            memtable_prefix_bloom_probes: u32)
+                                            -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_memtable_prefix_bloom_probes(self.options,
+                                                             memtable_prefix_bloom_probes)
+        };
+        self.settings.record("memtable_prefix_bloom_probes", memtable_prefix_bloom_probes);
+        self
+    }
+
+    /// Control the locality of bloom filter probes to improve cache hit rates. If non-zero, the
+    /// probes for a key are confined to this many cache lines.
+    ///
+    /// Default: 0
+    pub fn set_bloom_locality(&mut self, bloom_locality: u32) -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_bloom_locality(self.options, bloom_locality) };
+        self.settings.record("bloom_locality", bloom_locality);
+        self
+    }
+
+    /// Purge duplicate and deleted keys when a memtable is flushed to storage.
+    ///
+    /// Default: true
+    pub fn set_purge_redundant_kvs_while_flush(&mut self,
+                                               purge_redundant_kvs_while_flush: bool)
+                                               -> &mut ColumnFamilyOptions {
+        let purge = if purge_redundant_kvs_while_flush { 1 } else { 0 };
+        unsafe { rocksdb_options_set_purge_redundant_kvs_while_flush(self.options, purge) };
+        self.settings.record("purge_redundant_kvs_while_flush", purge_redundant_kvs_while_flush);
+        self
+    }
+
+    /// If true, compaction will verify the checksum of every read that happens as part of
+    /// compaction.
+    ///
+    /// Default: true
+    pub fn set_verify_checksums_in_compaction(&mut self,
+                                              verify_checksums_in_compaction: bool)
+                                              -> &mut ColumnFamilyOptions {
+        let verify = if verify_checksums_in_compaction { 1 } else { 0 };
+        unsafe { rocksdb_options_set_verify_checksums_in_compaction(self.options, verify) };
+        self.settings.record("verify_checksums_in_compaction", verify_checksums_in_compaction);
+        self
+    }
+
+    /// Use a key-mayExist filter for deletes. If true, a delete of a key which does not exist
+    /// according to the filter is not written. This may be useful for delete-heavy workloads, but
+    /// deleted keys may become visible again if a stale version remains on disk.
+    ///
+    /// Default: false
+    pub fn set_filter_deletes(&mut self, filter_deletes: bool) -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_filter_deletes(self.options, if filter_deletes { 1 } else { 0 });
+        }
+        self.settings.record("filter_deletes", filter_deletes);
+        self
+    }
+
+    /// An iteration which sequentially skips over more than this number of keys with the same user
+    /// key will issue a reseek.
+    ///
+    /// Default: 8
+    pub fn set_max_sequential_skip_in_iterations(&mut self,
+                                                 max_sequential_skip_in_iterations: u64)
+                                                 -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_max_sequential_skip_in_iterations(self.options,
+                                                                  max_sequential_skip_in_iterations)
+        };
+        self.settings.record("max_sequential_skip_in_iterations",
+                             max_sequential_skip_in_iterations);
+        self
+    }
+
+    /// Maximum number of successive merge operations on a key in the memtable.
+    ///
+    /// When a merge operation is added to the memtable and the maximum number of successive merges
+    /// is reached, the value of the key will be calculated and inserted into the memtable instead
+    /// of the merge operation. This will ensure that there are never more than
+    /// `max_successive_merges` merge operations in the memtable.
+    ///
+    /// Default: 0 (disabled)
+    pub fn set_max_successive_merges(&mut self,
+                                     max_successive_merges: u64)
+                                     -> &mut ColumnFamilyOptions {
+        unsafe { rocksdb_options_set_max_successive_merges(self.options, max_successive_merges) };
+        self.settings.record("max_successive_merges", max_successive_merges);
+        self
+    }
+
+    /// The number of merge operands to accumulate before `MergeOperator::partial_merge` will be
+    /// performed. If `min_partial_merge_operands` is less than 2, then it will be treated as 2.
+    ///
+    /// Default: 2
+    pub fn set_min_partial_merge_operands(&mut self,
+                                          min_partial_merge_operands: u32)
+                                          -> &mut ColumnFamilyOptions {
+        unsafe {
+            rocksdb_options_set_min_partial_merge_operands(self.options, min_partial_merge_operands)
+        };
+        self.settings.record("min_partial_merge_operands", min_partial_merge_operands);
+        self
+    }
+
+    /// Set a merge operator which merges decoded values and operands. See `set_merge_operator`
+    /// for the requirements on merge operators.
+    pub fn set_typed_merge_operator<V: Codec + Sync + Send,
+                                    Op: Codec + Sync + Send,
+                                    M: TypedMergeOperator<V, Op>>(&mut self,
+                                                                  name: &str,
+                                                                  merge_operator: M)
+                                                                  -> &mut ColumnFamilyOptions {
+        self.set_merge_operator(name, box TypedMergeOperatorAdapter::new(merge_operator))
+    }
+
+    /// Get the value of an option which has been set, formatted as in a RocksDB options file.
+    /// Options are named as in RocksDB options files, for example `write_buffer_size` or
+    /// `max_bytes_for_level_multiplier_additional`. Returns `None` if the option has not been set.
+    ///
+    /// The comparator and merge operator are reported by name. Options configured implicitly by
+    /// the `optimize_*` helpers are not reported individually; the helpers themselves are reported
+    /// under their own names.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.settings.get(name)
+    }
+
+    /// Get the name and value of every option which has been set, in the order they were set.
+    pub fn settings(&self) -> &[(String, String)] {
+        self.settings.settings.as_slice()
+    }
+
+    /// Set an option by name from its text value, as it would appear in a RocksDB options file.
+    /// See `get` for the option names. The comparator, merge operator and prefix extractor can not
+    /// be set by name.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<&mut ColumnFamilyOptions, String> {
+        match name {
+            "optimize_level_style_compaction" => {
+                self.optimize_level_style_compaction(try!(parse_option(name, value)));
+            },
+            "optimize_universal_style_compaction" => {
+                self.optimize_universal_style_compaction(try!(parse_option(name, value)));
+            },
+            "optimize_for_point_lookup" => {
+                self.optimize_for_point_lookup(try!(parse_option(name, value)));
+            },
+            "block_based_table_factory" => {
+                let table_options: BlockBasedTableOptions = try!(parse_option(name, value));
+                self.set_block_based_table_options(&table_options);
+            },
+            "plain_table_factory" => {
+                let table_options: PlainTableOptions = try!(parse_option(name, value));
+                self.set_plain_table_options(&table_options);
+            },
+            "write_buffer_size" => { self.set_write_buffer_size(try!(parse_option(name, value))); },
+            "max_write_buffer_number" => {
+                self.set_max_write_buffer_number(try!(parse_option(name, value)));
+            },
+            "min_write_buffer_number_to_merge" => {
+                self.set_min_write_buffer_number_to_merge(try!(parse_option(name, value)));
+            },
+            "num_levels" => { self.set_num_levels(try!(parse_option(name, value))); },
+            "level0_file_num_compaction_trigger" => {
+                self.set_level0_file_num_compaction_trigger(try!(parse_option(name, value)));
+            },
+            "level0_slowdown_writes_trigger" => {
+                self.set_level0_slowdown_writes_trigger(try!(parse_option(name, value)));
+            },
+            "level0_stop_writes_trigger" => {
+                self.set_level0_stop_writes_trigger(try!(parse_option(name, value)));
+            },
+            "max_mem_compaction_level" => {
+                self.set_max_mem_compaction_level(try!(parse_option(name, value)));
+            },
+            "target_file_size_base" => {
+                self.set_target_file_size_base(try!(parse_option(name, value)));
+            },
+            "target_file_size_multiplier" => {
+                self.set_target_file_size_multiplier(try!(parse_option(name, value)));
+            },
+            "max_bytes_for_level_base" => {
+                self.set_max_bytes_for_level_base(try!(parse_option(name, value)));
+            },
+            "max_bytes_for_level_multiplier" => {
+                self.set_max_bytes_for_level_multiplier(try!(parse_option(name, value)));
+            },
+            "max_bytes_for_level_multiplier_additional" => {
+                let mut multipliers = Vec::new();
+                for multiplier in value.split(':').filter(|multiplier| !multiplier.is_empty()) {
+                    multipliers.push(try!(parse_option(name, multiplier)));
+                }
+                self.set_max_bytes_for_level_multiplier_additional(multipliers.as_slice());
+            },
+            "expanded_compaction_factor" => {
+                self.set_expanded_compaction_factor(try!(parse_option(name, value)));
+            },
+            "source_compaction_factor" => {
+                self.set_source_compaction_factor(try!(parse_option(name, value)));
+            },
+            "max_grandparent_overlap_factor" => {
+                self.set_max_grandparent_overlap_factor(try!(parse_option(name, value)));
+            },
+            "soft_rate_limit" => { self.set_soft_rate_limit(try!(parse_option(name, value))); },
+            "hard_rate_limit" => { self.set_hard_rate_limit(try!(parse_option(name, value))); },
+            "rate_limit_delay_max_milliseconds" => {
+                let millis = try!(parse_option_in_range(name, value, 0, u32::MAX as i64));
+                self.set_rate_limit_delay_max(Duration::milliseconds(millis));
+            },
+            "disable_auto_compactions" => {
+                self.set_disable_auto_compactions(try!(parse_option(name, value)));
+            },
+            "compaction_style" => { self.set_compaction_style(try!(parse_option(name, value))); },
+            "compaction_options_universal" => {
+                let universal_options: UniversalCompactionOptions =
+                    try!(parse_option(name, value));
+                self.set_universal_compaction_options(&universal_options);
+            },
+            "compaction_options_fifo" => {
+                let fifo_options: FifoCompactionOptions = try!(parse_option(name, value));
+                self.set_fifo_compaction_options(&fifo_options);
+            },
+            "arena_block_size" => { self.set_arena_block_size(try!(parse_option(name, value))); },
+            "memtable_factory" => { self.set_memtable_rep(try!(parse_option(name, value))); },
+            "inplace_update_support" => {
+                self.set_inplace_update_support(try!(parse_option(name, value)));
+            },
+            "inplace_update_num_locks" => {
+                self.set_inplace_update_num_locks(try!(parse_option(name, value)));
+            },
+            "memtable_prefix_bloom_bits" => {
+                self.set_memtable_prefix_bloom_bits(try!(parse_option(name, value)));
+            },
+            "memtable_prefix_bloom_probes" => {
+                self.set_memtable_prefix_bloom_probes(try!(parse_option(name, value)));
+            },
+            "bloom_locality" => { self.set_bloom_locality(try!(parse_option(name, value))); },
+            "purge_redundant_kvs_while_flush" => {
+                self.set_purge_redundant_kvs_while_flush(try!(parse_option(name, value)));
+            },
+            "verify_checksums_in_compaction" => {
+                self.set_verify_checksums_in_compaction(try!(parse_option(name, value)));
+            },
+            "filter_deletes" => { self.set_filter_deletes(try!(parse_option(name, value))); },
+            "max_sequential_skip_in_iterations" => {
+                self.set_max_sequential_skip_in_iterations(try!(parse_option(name, value)));
+            },
+            "max_successive_merges" => {
+                self.set_max_successive_merges(try!(parse_option(name, value)));
+            },
+            "min_partial_merge_operands" => {
+                self.set_min_partial_merge_operands(try!(parse_option(name, value)));
+            },
+            "comparator" | "merge_operator" | "prefix_extractor" => {
+                return Err(format!("option {} must be configured in code", name))
+            },
+            _ => return Err(format!("unknown column family option: {}", name))
+        }
+        Ok(self)
+    }
+
+    /// Check that the options are consistent with each other and with the database options.
+    fn validate(&self, db_options: &DatabaseOptions) -> Result<(), String> {
+        let has_prefix_extractor = self.settings.get("prefix_extractor").is_some();
+
+        // The C API can not select the skip list representation, which is only the default.
+        if self.skip_list_restored {
+            return Err("the skip list memtable can not be restored once replaced".to_string());
+        }
+
+        let memtable_rep = self.settings.get("memtable_factory").unwrap_or("SkipListFactory");
+        if memtable_rep.starts_with("Hash") && !has_prefix_extractor {
+            return Err(format!("memtable {} requires a prefix extractor", memtable_rep));
+        }
+
+        if self.settings.get("compaction_style") == Some("kCompactionStyleFIFO")
+            && db_options.get("max_open_files") != Some("-1") {
+            return Err("FIFO-style compaction requires max_open_files to be unlimited".to_string());
+        }
+
+        // The table format is determined by whichever table option was set last.
+        let is_table_factory = |name: &str| {
+            name == "block_based_table_factory"
+                || name == "plain_table_factory"
+                || name == "optimize_for_point_lookup"
+        };
+        let table_factory = self.settings.settings.iter()
+                                                  .rev()
+                                                  .map(|&(ref name, _)| name.as_slice())
+                                                  .find(|&name| is_table_factory(name));
+        if table_factory == Some("plain_table_factory") {
+            if !has_prefix_extractor {
+                return Err("plain table format requires a prefix extractor".to_string());
+            }
+            if db_options.get("allow_mmap_reads") != Some("true") {
+                return Err("plain table format requires memory mapped reads".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the raw `rocksdb_options_t` struct.
+    fn options(&self) -> *const rocksdb_options_t {
+        self.options as *const rocksdb_options_t
+    }
+}
+
+/// The memtable memory budget used by presets which configure level-style compaction.
+static DEFAULT_MEMTABLE_MEMORY_BUDGET: u64 = 512 << 20;
+
+/// The data structure used to hold a memtable.
+#[deriving(Clone, PartialEq, Eq)]
+pub enum MemtableRep {
+    /// A skip list. Supports efficient concurrent reads and writes, and iteration in key order.
+    SkipListRep,
+    /// A vector which is sorted when the memtable is flushed or iterated. Suitable for bulk
+    /// loads, where reads do not occur until loading is complete.
+    VectorRep,
+    /// A hash table of skip lists, one for each key prefix, with the provided bucket count, skip
+    /// list height and skip list branching factor. Efficient for lookups and scans within a
+    /// prefix. Requires a prefix extractor.
+    HashSkipListRep(u64, i32, i32),
+    /// A hash table of sorted linked lists, one for each key prefix, with the provided bucket
+    /// count. Efficient for lookups when prefixes have few keys. Requires a prefix extractor.
+    HashLinkListRep(u64)
+}
+
+/// Formats the memtable representation with the factory name used in RocksDB options files,
+/// followed by any parameters separated by ':'.
+impl fmt::Show for MemtableRep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SkipListRep => write!(f, "SkipListFactory"),
+            VectorRep => write!(f, "VectorRepFactory"),
+            HashSkipListRep(bucket_count, skiplist_height, skiplist_branching_factor) => {
+                write!(f, "HashSkipListRepFactory:{}:{}:{}",
+                       bucket_count, skiplist_height, skiplist_branching_factor)
+            },
+            HashLinkListRep(bucket_count) => write!(f, "HashLinkListRepFactory:{}", bucket_count)
+        }
+    }
+}
 
-    /// Create a new database options struct for specifying configuration to use when opening or
-    /// creating a database.
-    pub fn new() -> DatabaseOptions {
-        let options = unsafe { rocksdb_options_create() };
-        DatabaseOptions { options: options }
+impl FromStr for MemtableRep {
+    fn from_str(s: &str) -> Option<MemtableRep> {
+        let parts: Vec<&str> = s.split(':').collect();
+        match (parts[0], parts.len()) {
+            ("SkipListFactory", 1) => Some(SkipListRep),
+            ("VectorRepFactory", 1) => Some(VectorRep),
+            ("HashSkipListRepFactory", 4) => {
+                match (from_str(parts[1]), from_str(parts[2]), from_str(parts[3])) {
+                    (Some(bucket_count), Some(height), Some(branching_factor)) => {
+                        Some(HashSkipListRep(bucket_count, height, branching_factor))
+                    },
+                    _ => None
+                }
+            },
+            ("HashLinkListRepFactory", 2) => from_str(parts[1]).map(HashLinkListRep),
+            _ => None
+        }
     }
+}
 
-    /// By default, RocksDB uses a single background thread for flush and compaction. Calling this
-    /// function will set it up such that `total_threads` number of threads is used. A good value
-    /// for `total_threads` is the number of cores. You should call this function if your system is
-    /// bottlenecked by RocksDB.
-    pub fn increase_parallelism(&mut self, total_threads: i32) -> &mut DatabaseOptions {
-        unsafe { rocksdb_options_increase_parallelism(self.options, total_threads) };
+/// Options for the block-based table format. Options which are not set keep the RocksDB defaults.
+///
+/// Formatted in options files as a list of settings in braces, for example
+/// `{block_size=65536;bloom_filter_bits_per_key=10}`.
+#[deriving(Clone, PartialEq)]
+pub struct BlockBasedTableOptions {
+    block_size: Option<u64>,
+    block_restart_interval: Option<i32>,
+    bloom_filter_bits_per_key: Option<i32>,
+    block_cache_size: Option<u64>,
+    no_block_cache: bool,
+    whole_key_filtering: Option<bool>
+}
+
+impl BlockBasedTableOptions {
+
+    pub fn new() -> BlockBasedTableOptions {
+        BlockBasedTableOptions { block_size: None,
+                                 block_restart_interval: None,
+                                 bloom_filter_bits_per_key: None,
+                                 block_cache_size: None,
+                                 no_block_cache: false,
+                                 whole_key_filtering: None }
+    }
+
+    /// Approximate size of user data packed per block, in bytes. Note that the block size
+    /// specified here corresponds to uncompressed data.
+    ///
+    /// Default: 4KB
+    pub fn set_block_size(&mut self, block_size: u64) -> &mut BlockBasedTableOptions {
+        self.block_size = Some(block_size);
         self
     }
 
-    /// Configure the database for bulk-writing. All data will be flushed to level 0 without
-    /// automatic compaction. It is recommended to manually compact the database before resuming
-    /// reads.
-    pub fn prepare_for_bulkload(&mut self) -> &mut DatabaseOptions {
-        unsafe { rocksdb_options_prepare_for_bulk_load(self.options) };
+    /// Number of keys between restart points for delta encoding of keys.
+    ///
+    /// Default: 16
+    pub fn set_block_restart_interval(&mut self,
+                                      block_restart_interval: i32)
+                                      -> &mut BlockBasedTableOptions {
+        self.block_restart_interval = Some(block_restart_interval);
         self
     }
 
-    /// Get the raw `rocksdb_options_t` struct.
-    fn options(&self) -> *const rocksdb_options_t {
-        self.options as *const rocksdb_options_t
+    /// Use a bloom filter with `bits_per_key` bits per key to reduce disk reads for lookups of
+    /// keys which are not present. 10 bits per key yields a filter with a false positive rate of
+    /// approximately 1%.
+    ///
+    /// Default: no filter
+    pub fn set_bloom_filter_bits_per_key(&mut self,
+                                         bits_per_key: i32)
+                                         -> &mut BlockBasedTableOptions {
+        self.bloom_filter_bits_per_key = Some(bits_per_key);
+        self
     }
 
-    /// Mutably get the raw `rocksdb_options_t` struct.
-    fn options_mut(&self) -> *mut rocksdb_options_t {
-        self.options
+    /// Use an LRU block cache of `block_cache_size` bytes for uncompressed blocks.
+    ///
+    /// Default: an 8MB block cache
+    pub fn set_block_cache_size(&mut self, block_cache_size: u64) -> &mut BlockBasedTableOptions {
+        self.block_cache_size = Some(block_cache_size);
+        self.no_block_cache = false;
+        self
+    }
+
+    /// Disable the block cache.
+    pub fn set_no_block_cache(&mut self) -> &mut BlockBasedTableOptions {
+        self.block_cache_size = None;
+        self.no_block_cache = true;
+        self
+    }
+
+    /// If true, place whole keys in the filter (not just prefixes). This must generally be true
+    /// for gets to be efficient.
+    ///
+    /// Default: true
+    pub fn set_whole_key_filtering(&mut self,
+                                   whole_key_filtering: bool)
+                                   -> &mut BlockBasedTableOptions {
+        self.whole_key_filtering = Some(whole_key_filtering);
+        self
+    }
+
+    /// Create the raw `rocksdb_block_based_table_options_t` struct. The caller is responsible for
+    /// destroying it.
+    unsafe fn create(&self) -> *mut rocksdb_block_based_table_options_t {
+        let options = rocksdb_block_based_options_create();
+        for &block_size in self.block_size.iter() {
+            rocksdb_block_based_options_set_block_size(options, block_size);
+        }
+        for &block_restart_interval in self.block_restart_interval.iter() {
+            rocksdb_block_based_options_set_block_restart_interval(options, block_restart_interval);
+        }
+        for &bits_per_key in self.bloom_filter_bits_per_key.iter() {
+            // The table options take ownership of the filter policy.
+            rocksdb_block_based_options_set_filter_policy(
+                options, rocksdb_filterpolicy_create_bloom(bits_per_key));
+        }
+        for &block_cache_size in self.block_cache_size.iter() {
+            // The table options retain a reference to the cache, so the handle can be destroyed.
+            let cache = rocksdb_cache_create_lru(block_cache_size);
+            rocksdb_block_based_options_set_block_cache(options, cache);
+            rocksdb_cache_destroy(cache);
+        }
+        if self.no_block_cache {
+            rocksdb_block_based_options_set_no_block_cache(options, 1);
+        }
+        for &whole_key_filtering in self.whole_key_filtering.iter() {
+            let whole_key_filtering = if whole_key_filtering { 1 } else { 0 };
+            rocksdb_block_based_options_set_whole_key_filtering(options, whole_key_filtering);
+        }
+        options
     }
 }
 
-/// Options for opening or creating a column family in a RocksDB database.
-pub struct ColumnFamilyOptions {
-    options: *mut rocksdb_options_t,
-    comparator: Option<Comparator>
+impl fmt::Show for BlockBasedTableOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut settings = Vec::new();
+        for block_size in self.block_size.iter() {
+            settings.push(format!("block_size={}", block_size));
+        }
+        for block_restart_interval in self.block_restart_interval.iter() {
+            settings.push(format!("block_restart_interval={}", block_restart_interval));
+        }
+        for bits_per_key in self.bloom_filter_bits_per_key.iter() {
+            settings.push(format!("bloom_filter_bits_per_key={}", bits_per_key));
+        }
+        for block_cache_size in self.block_cache_size.iter() {
+            settings.push(format!("block_cache_size={}", block_cache_size));
+        }
+        if self.no_block_cache {
+            settings.push("no_block_cache=true".to_string());
+        }
+        for whole_key_filtering in self.whole_key_filtering.iter() {
+            settings.push(format!("whole_key_filtering={}", whole_key_filtering));
+        }
+        write!(f, "{{{}}}", settings.connect(";"))
+    }
 }
 
-impl Drop for ColumnFamilyOptions {
-    fn drop(&mut self) {
-        debug!("ColumnFamilyOptions::drop");
-        unsafe { rocksdb_options_destroy(self.options) }
+impl FromStr for BlockBasedTableOptions {
+    fn from_str(s: &str) -> Option<BlockBasedTableOptions> {
+        let settings = match parse_nested_settings(s) {
+            Some(settings) => settings,
+            None => return None
+        };
+        let mut options = BlockBasedTableOptions::new();
+        for &(name, value) in settings.iter() {
+            let parsed = match name {
+                "block_size" => from_str(value).map(|v| { options.set_block_size(v); }),
+                "block_restart_interval" => {
+                    from_str(value).map(|v| { options.set_block_restart_interval(v); })
+                },
+                "bloom_filter_bits_per_key" => {
+                    from_str(value).map(|v| { options.set_bloom_filter_bits_per_key(v); })
+                },
+                "block_cache_size" => from_str(value).map(|v| { options.set_block_cache_size(v); }),
+                "no_block_cache" => {
+                    from_str(value).map(|v: bool| if v { options.set_no_block_cache(); })
+                },
+                "whole_key_filtering" => {
+                    from_str(value).map(|v| { options.set_whole_key_filtering(v); })
+                },
+                _ => None
+            };
+            if parsed.is_none() {
+                return None;
+            }
+        }
+        Some(options)
     }
 }
 
-impl ColumnFamilyOptions {
+/// The compaction style of a column family.
+#[deriving(Clone, PartialEq, Eq)]
+pub enum CompactionStyle {
+    /// Level-style compaction, with low space amplification and higher write amplification.
+    CompactionStyleLevel = 0,
+    /// Universal-style compaction, with low write amplification and higher space amplification.
+    CompactionStyleUniversal = 1,
+    /// FIFO-style compaction, which deletes the oldest files once the total size of the column
+    /// family exceeds a bound, without merging files. Suitable for data with a limited lifetime,
+    /// such as event logs. Requires the database to keep all table files open (see
+    /// `DatabaseOptions::set_max_open_files`), which is checked when the database is opened.
+    CompactionStyleFifo = 2
+}
 
-    /// Create a new column family options struct for specifying configuration to use when opening
-    /// or creating a column family.
-    pub fn new() -> ColumnFamilyOptions {
-        let options = unsafe { rocksdb_options_create() };
-        ColumnFamilyOptions { options: options, comparator: None }
+/// Formats the compaction style with the name used in RocksDB options files.
+impl fmt::Show for CompactionStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            CompactionStyleLevel => "kCompactionStyleLevel",
+            CompactionStyleUniversal => "kCompactionStyleUniversal",
+            CompactionStyleFifo => "kCompactionStyleFIFO"
+        };
+        f.pad(name)
     }
+}
 
-    /// Configure the column family to use level-style compaction with a memtable of size
-    /// `memtable_size`.
-    pub fn optimize_level_style_compaction(&mut self,
-                                           memtable_size: u64)
-                                           -> &mut ColumnFamilyOptions {
-        unsafe { rocksdb_options_optimize_level_style_compaction(self.options, memtable_size) };
+impl FromStr for CompactionStyle {
+    fn from_str(s: &str) -> Option<CompactionStyle> {
+        match s {
+            "kCompactionStyleLevel" => Some(CompactionStyleLevel),
+            "kCompactionStyleUniversal" => Some(CompactionStyleUniversal),
+            "kCompactionStyleFIFO" => Some(CompactionStyleFifo),
+            _ => None
+        }
+    }
+}
+
+/// The algorithm used by universal-style compaction to pick the files to compact.
+#[deriving(Clone, PartialEq, Eq)]
+pub enum UniversalCompactionStopStyle {
+    /// Stop adding files to a compaction when the next file is larger than the files picked so
+    /// far by more than the size ratio.
+    StopStyleSimilarSize = 0,
+    /// Stop adding files to a compaction when the total size of the files picked so far is
+    /// smaller than the next file by more than the size ratio.
+    StopStyleTotalSize = 1
+}
+
+/// Formats the stop style with the name used in RocksDB options files.
+impl fmt::Show for UniversalCompactionStopStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            StopStyleSimilarSize => "kCompactionStopStyleSimilarSize",
+            StopStyleTotalSize => "kCompactionStopStyleTotalSize"
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for UniversalCompactionStopStyle {
+    fn from_str(s: &str) -> Option<UniversalCompactionStopStyle> {
+        match s {
+            "kCompactionStopStyleSimilarSize" => Some(StopStyleSimilarSize),
+            "kCompactionStopStyleTotalSize" => Some(StopStyleTotalSize),
+            _ => None
+        }
+    }
+}
+
+/// Options for universal-style compaction. Options which are not set keep the RocksDB defaults.
+///
+/// Formatted in options files as a list of settings in braces, for example
+/// `{size_ratio=10;max_merge_width=8}`.
+#[deriving(Clone, PartialEq)]
+pub struct UniversalCompactionOptions {
+    size_ratio: Option<i32>,
+    min_merge_width: Option<i32>,
+    max_merge_width: Option<i32>,
+    max_size_amplification_percent: Option<i32>,
+    compression_size_percent: Option<i32>,
+    stop_style: Option<UniversalCompactionStopStyle>
+}
+
+impl UniversalCompactionOptions {
+
+    pub fn new() -> UniversalCompactionOptions {
+        UniversalCompactionOptions { size_ratio: None,
+                                     min_merge_width: None,
+                                     max_merge_width: None,
+                                     max_size_amplification_percent: None,
+                                     compression_size_percent: None,
+                                     stop_style: None }
+    }
+
+    /// Percentage flexibility while comparing file sizes. If the candidate file is smaller than
+    /// the next file by at most this percentage, the next file is included in the compaction.
+    ///
+    /// Default: 1
+    pub fn set_size_ratio(&mut self, size_ratio: i32) -> &mut UniversalCompactionOptions {
+        self.size_ratio = Some(size_ratio);
         self
     }
 
-    /// Configure the column family to use universal-style compaction with a memtable of size
-    /// `memtable_size`.
-    pub fn optimize_universal_style_compaction(&mut self,
-                                               memtable_size: u64)
-                                               -> &mut ColumnFamilyOptions {
-        unsafe { rocksdb_options_optimize_universal_style_compaction(self.options, memtable_size) };
+    /// The minimum number of files in a single compaction run.
+    ///
+    /// Default: 2
+    pub fn set_min_merge_width(&mut self, min_merge_width: i32) -> &mut UniversalCompactionOptions {
+        self.min_merge_width = Some(min_merge_width);
         self
     }
 
-    /// Comparator used to define the order of keys in the table.
-    /// Default: a comparator that uses lexicographic byte-wise ordering
+    /// The maximum number of files in a single compaction run.
     ///
-    /// REQUIRES: The client must ensure that the comparator supplied
-    /// here has the same name and orders keys *exactly* the same as the
-    /// comparator provided to previous open calls on the same DB.
-    pub fn set_comparator(&mut self,
-                          name: &str,
-                          compare: |&[u8], &[u8]|: Sync + Send -> Ordering)
-                          -> &mut ColumnFamilyOptions {
-        let comparator = Comparator::new(name, compare);
-        unsafe { rocksdb_options_set_comparator(self.options, comparator.comparator) };
-        self.comparator = Some(comparator);
+    /// Default: unlimited
+    pub fn set_max_merge_width(&mut self, max_merge_width: i32) -> &mut UniversalCompactionOptions {
+        self.max_merge_width = Some(max_merge_width);
         self
     }
 
-    /// REQUIRES: The client must provide a merge operator if Merge operation
-    /// needs to be accessed. Calling Merge on a DB without a merge operator
-    /// would result in Status::NotSupported. The client must ensure that the
-    /// merge operator supplied here has the same name and *exactly* the same
-    /// semantics as the merge operator provided to previous open calls on
-    /// the same DB. The only exception is reserved for upgrade, where a DB
-    /// previously without a merge operator is introduced to Merge operation
-    /// for the first time. It's necessary to specify a merge operator when
-    /// openning the DB in this case.
-    /// Default: nullptr
-    pub fn set_merge_operator(&mut self,
-                              name: &str,
-                              merge_operator: Box<MergeOperator>)
-                              -> &mut ColumnFamilyOptions {
-        let merge_operator = MergeOperatorState::new(name, merge_operator);
-        unsafe { rocksdb_options_set_merge_operator(self.options, merge_operator) };
+    /// The size amplification is the amount of additional storage, as a percentage, needed to
+    /// store a byte of data. For example, a size amplification of 2% means that a database which
+    /// holds 100 bytes of user data may occupy up to 102 bytes of storage. When the size
+    /// amplification exceeds this limit, all files are compacted together.
+    ///
+    /// Default: 200
+    pub fn set_max_size_amplification_percent(&mut self,
+                                              max_size_amplification_percent: i32)
+                                              -> &mut UniversalCompactionOptions {
+        self.max_size_amplification_percent = Some(max_size_amplification_percent);
         self
     }
 
-    /// Get the raw `rocksdb_options_t` struct.
-    fn options(&self) -> *const rocksdb_options_t {
-        self.options as *const rocksdb_options_t
+    /// If set to a non-negative value, compression is only applied to the oldest files, such that
+    /// at least this percentage of the data is compressed. If negative, all output files follow
+    /// the compression type of the column family.
+    ///
+    /// Default: -1
+    pub fn set_compression_size_percent(&mut self,
+                                        compression_size_percent: i32)
+                                        -> &mut UniversalCompactionOptions {
+        self.compression_size_percent = Some(compression_size_percent);
+        self
+    }
+
+    /// The algorithm used to stop picking files into a single compaction run.
+    ///
+    /// Default: `StopStyleTotalSize`
+    pub fn set_stop_style(&mut self,
+                          stop_style: UniversalCompactionStopStyle)
+                          -> &mut UniversalCompactionOptions {
+        self.stop_style = Some(stop_style);
+        self
+    }
+
+    /// Create the raw `rocksdb_universal_compaction_options_t` struct. The caller is responsible
+    /// for destroying it.
+    unsafe fn create(&self) -> *mut rocksdb_universal_compaction_options_t {
+        let options = rocksdb_universal_compaction_options_create();
+        for &size_ratio in self.size_ratio.iter() {
+            rocksdb_universal_compaction_options_set_size_ratio(options, size_ratio);
+        }
+        for &min_merge_width in self.min_merge_width.iter() {
+            rocksdb_universal_compaction_options_set_min_merge_width(options, min_merge_width);
+        }
+        for &max_merge_width in self.max_merge_width.iter() {
+            rocksdb_universal_compaction_options_set_max_merge_width(options, max_merge_width);
+        }
+        for &percent in self.max_size_amplification_percent.iter() {
+            rocksdb_universal_compaction_options_set_max_size_amplification_percent(options,
+                                                                                    percent);
+        }
+        for &percent in self.compression_size_percent.iter() {
+            rocksdb_universal_compaction_options_set_compression_size_percent(options, percent);
+        }
+        for stop_style in self.stop_style.iter() {
+            rocksdb_universal_compaction_options_set_stop_style(options,
+                                                                stop_style.clone() as i32);
+        }
+        options
+    }
+}
+
+impl fmt::Show for UniversalCompactionOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut settings = Vec::new();
+        for size_ratio in self.size_ratio.iter() {
+            settings.push(format!("size_ratio={}", size_ratio));
+        }
+        for min_merge_width in self.min_merge_width.iter() {
+            settings.push(format!("min_merge_width={}", min_merge_width));
+        }
+        for max_merge_width in self.max_merge_width.iter() {
+            settings.push(format!("max_merge_width={}", max_merge_width));
+        }
+        for percent in self.max_size_amplification_percent.iter() {
+            settings.push(format!("max_size_amplification_percent={}", percent));
+        }
+        for percent in self.compression_size_percent.iter() {
+            settings.push(format!("compression_size_percent={}", percent));
+        }
+        for stop_style in self.stop_style.iter() {
+            settings.push(format!("stop_style={}", stop_style));
+        }
+        write!(f, "{{{}}}", settings.connect(";"))
+    }
+}
+
+impl FromStr for UniversalCompactionOptions {
+    fn from_str(s: &str) -> Option<UniversalCompactionOptions> {
+        let settings = match parse_nested_settings(s) {
+            Some(settings) => settings,
+            None => return None
+        };
+        let mut options = UniversalCompactionOptions::new();
+        for &(name, value) in settings.iter() {
+            let parsed = match name {
+                "size_ratio" => from_str(value).map(|v| { options.set_size_ratio(v); }),
+                "min_merge_width" => from_str(value).map(|v| { options.set_min_merge_width(v); }),
+                "max_merge_width" => from_str(value).map(|v| { options.set_max_merge_width(v); }),
+                "max_size_amplification_percent" => {
+                    from_str(value).map(|v| { options.set_max_size_amplification_percent(v); })
+                },
+                "compression_size_percent" => {
+                    from_str(value).map(|v| { options.set_compression_size_percent(v); })
+                },
+                "stop_style" => from_str(value).map(|v| { options.set_stop_style(v); }),
+                _ => None
+            };
+            if parsed.is_none() {
+                return None;
+            }
+        }
+        Some(options)
+    }
+}
+
+/// Options for FIFO-style compaction. Options which are not set keep the RocksDB defaults.
+///
+/// Formatted in options files as a list of settings in braces, for example
+/// `{max_table_files_size=1073741824}`.
+#[deriving(Clone, PartialEq)]
+pub struct FifoCompactionOptions {
+    max_table_files_size: Option<u64>
+}
+
+impl FifoCompactionOptions {
+
+    pub fn new() -> FifoCompactionOptions {
+        FifoCompactionOptions { max_table_files_size: None }
+    }
+
+    /// Once the total size of the table files in the column family exceeds this size, in bytes,
+    /// the oldest table file is deleted. Keys written since are not affected, so data ages out
+    /// without tombstones.
+    ///
+    /// Default: 1GB
+    pub fn set_max_table_files_size(&mut self,
+                                    max_table_files_size: u64)
+                                    -> &mut FifoCompactionOptions {
+        self.max_table_files_size = Some(max_table_files_size);
+        self
+    }
+
+    /// Create the raw `rocksdb_fifo_compaction_options_t` struct. The caller is responsible for
+    /// destroying it.
+    unsafe fn create(&self) -> *mut rocksdb_fifo_compaction_options_t {
+        let options = rocksdb_fifo_compaction_options_create();
+        for &max_table_files_size in self.max_table_files_size.iter() {
+            rocksdb_fifo_compaction_options_set_max_table_files_size(options,
+                                                                     max_table_files_size);
+        }
+        options
+    }
+}
+
+impl fmt::Show for FifoCompactionOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut settings = Vec::new();
+        for max_table_files_size in self.max_table_files_size.iter() {
+            settings.push(format!("max_table_files_size={}", max_table_files_size));
+        }
+        write!(f, "{{{}}}", settings.connect(";"))
+    }
+}
+
+impl FromStr for FifoCompactionOptions {
+    fn from_str(s: &str) -> Option<FifoCompactionOptions> {
+        let settings = match parse_nested_settings(s) {
+            Some(settings) => settings,
+            None => return None
+        };
+        let mut options = FifoCompactionOptions::new();
+        for &(name, value) in settings.iter() {
+            let parsed = match name {
+                "max_table_files_size" => {
+                    from_str(value).map(|v| { options.set_max_table_files_size(v); })
+                },
+                _ => None
+            };
+            if parsed.is_none() {
+                return None;
+            }
+        }
+        Some(options)
+    }
+}
+
+/// Options for the plain table format.
+///
+/// Formatted in options files as a list of settings in braces, for example
+/// `{user_key_len=16;bloom_bits_per_key=10;hash_table_ratio=0.75;index_sparseness=16}`.
+#[deriving(Clone, PartialEq)]
+pub struct PlainTableOptions {
+    user_key_len: u32,
+    bloom_bits_per_key: i32,
+    hash_table_ratio: f64,
+    index_sparseness: u64
+}
+
+impl PlainTableOptions {
+
+    pub fn new() -> PlainTableOptions {
+        PlainTableOptions { user_key_len: 0,
+                            bloom_bits_per_key: 10,
+                            hash_table_ratio: 0.75,
+                            index_sparseness: 16 }
+    }
+
+    /// The length of every key in the column family, in bytes, or 0 if keys have variable
+    /// length. Fixed length keys are stored more compactly.
+    ///
+    /// Default: 0
+    pub fn set_user_key_len(&mut self, user_key_len: u32) -> &mut PlainTableOptions {
+        self.user_key_len = user_key_len;
+        self
+    }
+
+    /// Number of bits per prefix used by the prefix bloom filter, or 0 to disable the filter.
+    ///
+    /// Default: 10
+    pub fn set_bloom_bits_per_key(&mut self, bloom_bits_per_key: i32) -> &mut PlainTableOptions {
+        self.bloom_bits_per_key = bloom_bits_per_key;
+        self
+    }
+
+    /// The desired utilization of the prefix hash table, between 0 and 1. Smaller values reduce
+    /// hash collisions at the cost of memory. If 0, the hash table is not used, and lookups
+    /// binary search the prefix index instead.
+    ///
+    /// Default: 0.75
+    pub fn set_hash_table_ratio(&mut self, hash_table_ratio: f64) -> &mut PlainTableOptions {
+        self.hash_table_ratio = hash_table_ratio;
+        self
+    }
+
+    /// Number of keys within a prefix between index records. Lookups linearly scan up to this
+    /// many keys after the closest index record.
+    ///
+    /// Default: 16
+    pub fn set_index_sparseness(&mut self, index_sparseness: u64) -> &mut PlainTableOptions {
+        self.index_sparseness = index_sparseness;
+        self
+    }
+}
+
+impl fmt::Show for PlainTableOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{user_key_len={};bloom_bits_per_key={};hash_table_ratio={};\
+                   index_sparseness={}}}",
+               self.user_key_len, self.bloom_bits_per_key, self.hash_table_ratio,
+               self.index_sparseness)
+    }
+}
+
+impl FromStr for PlainTableOptions {
+    fn from_str(s: &str) -> Option<PlainTableOptions> {
+        let settings = match parse_nested_settings(s) {
+            Some(settings) => settings,
+            None => return None
+        };
+        let mut options = PlainTableOptions::new();
+        for &(name, value) in settings.iter() {
+            let parsed = match name {
+                "user_key_len" => from_str(value).map(|v| { options.set_user_key_len(v); }),
+                "bloom_bits_per_key" => {
+                    from_str(value).map(|v| { options.set_bloom_bits_per_key(v); })
+                },
+                "hash_table_ratio" => from_str(value).map(|v| { options.set_hash_table_ratio(v); }),
+                "index_sparseness" => from_str(value).map(|v| { options.set_index_sparseness(v); }),
+                _ => None
+            };
+            if parsed.is_none() {
+                return None;
+            }
+        }
+        Some(options)
     }
 }
 
@@ -924,3 +4216,40 @@ impl ReadOptions {
         self.options as *const rocksdb_readoptions_t
     }
 }
+
+/// Options for flushing memtables to storage.
+pub struct FlushOptions {
+  options: *mut rocksdb_flushoptions_t
+}
+
+impl Drop for FlushOptions {
+    fn drop(&mut self) {
+        unsafe { rocksdb_flushoptions_destroy(self.options); }
+    }
+}
+
+impl FlushOptions {
+
+    /// Create a new flush options struct for specifying configuration to use when flushing a
+    /// RocksDB database.
+    pub fn new() -> FlushOptions {
+        unsafe {
+            let options = rocksdb_flushoptions_create();
+            FlushOptions { options: options }
+        }
+    }
+
+    /// If true, the flush will wait until the memtable has been written to a table file before
+    /// returning. If false, the flush is scheduled in the background.
+    ///
+    /// Default: true
+    pub fn set_wait(&mut self, wait: bool) -> &mut FlushOptions {
+        unsafe { rocksdb_flushoptions_set_wait(self.options, if wait { 1 } else { 0 }); }
+        self
+    }
+
+    /// Get the raw `rocksdb_flushoptions_t` struct.
+    fn options(&self) -> *const rocksdb_flushoptions_t {
+        self.options as *const rocksdb_flushoptions_t
+    }
+}
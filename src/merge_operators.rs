@@ -1,4 +1,10 @@
+use serialize::json;
+use std::cmp;
+use std::collections::{HashSet, TreeMap, TreeSet};
 use std::io;
+use std::num::Float;
+use std::str;
+use std::uint;
 
 use super::{AssociativeMergeOperator, MergeOperator, Operands};
 
@@ -66,3 +72,809 @@ impl AssociativeMergeOperator for AddMergeOperator {
         AddMergeOperator::write_u64(existing + operand)
     }
 }
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+//// Collection Merge Operators
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Values and operands of the collection merge operators are built from length-prefixed elements:
+// a big-endian `u32` byte length followed by the raw element bytes. A value is a sequence of such
+// elements. Set operands additionally prefix every element with a single tag byte describing the
+// operation (`0` for add, `1` for remove). An operand may contain any number of elements, so
+// operands built with the helper functions can be concatenated into a single operand.
+
+static SET_ADD: u8 = 0;
+static SET_REMOVE: u8 = 1;
+
+fn invalid_operand(desc: &'static str) -> io::IoError {
+    io::IoError { kind: io::InvalidInput, desc: desc, detail: None }
+}
+
+/// Write a single length-prefixed element.
+fn write_element<W: Writer>(writer: &mut W, element: &[u8]) -> io::IoResult<()> {
+    try!(writer.write_be_u32(element.len() as u32));
+    writer.write(element)
+}
+
+/// Encode a sequence of elements as length-prefixed elements.
+fn write_elements<'a, I: Iterator<&'a Vec<u8>>>(mut elements: I) -> io::IoResult<Vec<u8>> {
+    let mut writer = io::MemWriter::new();
+    for element in elements {
+        try!(write_element(&mut writer, element.as_slice()));
+    }
+    Ok(writer.unwrap())
+}
+
+/// Decode a sequence of length-prefixed elements.
+fn read_elements(bytes: &[u8]) -> io::IoResult<Vec<Vec<u8>>> {
+    let mut reader = io::BufReader::new(bytes);
+    let mut elements = Vec::new();
+    while !reader.eof() {
+        let len = try!(reader.read_be_u32());
+        elements.push(try!(reader.read_exact(len as uint)));
+    }
+    Ok(elements)
+}
+
+/// Decode a sequence of tagged set operations.
+fn read_set_operations(bytes: &[u8]) -> io::IoResult<Vec<(u8, Vec<u8>)>> {
+    let mut reader = io::BufReader::new(bytes);
+    let mut operations = Vec::new();
+    while !reader.eof() {
+        let op = try!(reader.read_u8());
+        if op != SET_ADD && op != SET_REMOVE {
+            return Err(invalid_operand("unknown set operation"));
+        }
+        let len = try!(reader.read_be_u32());
+        operations.push((op, try!(reader.read_exact(len as uint))));
+    }
+    Ok(operations)
+}
+
+/// Maintains a sorted set of byte strings.
+///
+/// The value is the sorted, de-duplicated sequence of set members. Operands add or remove members;
+/// build them with `SetMergeOperator::add` and `SetMergeOperator::remove`. Removing a member that
+/// is not present is not an error.
+pub struct SetMergeOperator;
+
+impl SetMergeOperator {
+
+    /// Create an operand which adds `element` to the set.
+    pub fn add(element: &[u8]) -> io::IoResult<Vec<u8>> {
+        SetMergeOperator::operation(SET_ADD, element)
+    }
+
+    /// Create an operand which removes `element` from the set.
+    pub fn remove(element: &[u8]) -> io::IoResult<Vec<u8>> {
+        SetMergeOperator::operation(SET_REMOVE, element)
+    }
+
+    /// Read the members of a set value, in sorted order.
+    pub fn read_set(bytes: &[u8]) -> io::IoResult<Vec<Vec<u8>>> {
+        read_elements(bytes)
+    }
+
+    fn operation(op: u8, element: &[u8]) -> io::IoResult<Vec<u8>> {
+        let mut writer = io::MemWriter::with_capacity(element.len() + 5);
+        try!(writer.write_u8(op));
+        try!(write_element(&mut writer, element));
+        Ok(writer.unwrap())
+    }
+}
+
+impl MergeOperator for SetMergeOperator {
+
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  mut operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        let mut set: TreeSet<Vec<u8>> = match existing_val {
+            Some(val) => try!(read_elements(val)).into_iter().collect(),
+            None => TreeSet::new()
+        };
+
+        for operand in operands {
+            for (op, element) in try!(read_set_operations(operand)).into_iter() {
+                if op == SET_ADD {
+                    set.insert(element);
+                } else {
+                    set.remove(&element);
+                }
+            }
+        }
+
+        write_elements(set.iter())
+    }
+
+    fn partial_merge(&self,
+                     _key: &[u8],
+                     mut operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        // Operations on distinct elements commute, so only the last operation on each element
+        // needs to be retained.
+        let mut operations: TreeMap<Vec<u8>, u8> = TreeMap::new();
+        for operand in operands {
+            for (op, element) in try!(read_set_operations(operand)).into_iter() {
+                operations.insert(element, op);
+            }
+        }
+
+        let mut writer = io::MemWriter::new();
+        for (element, &op) in operations.iter() {
+            try!(writer.write_u8(op));
+            try!(write_element(&mut writer, element.as_slice()));
+        }
+        Ok(writer.unwrap())
+    }
+}
+
+/// Maintains a list of byte strings which retains only the `max_len` most recently appended
+/// elements.
+///
+/// The value is the sequence of list elements, oldest first. Operands are created with
+/// `BoundedListMergeOperator::append`.
+pub struct BoundedListMergeOperator {
+    max_len: uint
+}
+
+impl BoundedListMergeOperator {
+
+    /// Create a bounded list merge operator which keeps the last `max_len` elements.
+    pub fn new(max_len: uint) -> BoundedListMergeOperator {
+        BoundedListMergeOperator { max_len: max_len }
+    }
+
+    /// Create an operand which appends `elements` to the list.
+    pub fn append(elements: &[&[u8]]) -> io::IoResult<Vec<u8>> {
+        let mut writer = io::MemWriter::new();
+        for element in elements.iter() {
+            try!(write_element(&mut writer, *element));
+        }
+        Ok(writer.unwrap())
+    }
+
+    /// Read the elements of a list value, oldest first.
+    pub fn read_list(bytes: &[u8]) -> io::IoResult<Vec<Vec<u8>>> {
+        read_elements(bytes)
+    }
+
+    fn truncate(&self, list: Vec<Vec<u8>>) -> io::IoResult<Vec<u8>> {
+        let skip = if list.len() > self.max_len { list.len() - self.max_len } else { 0 };
+        write_elements(list.iter().skip(skip))
+    }
+}
+
+impl MergeOperator for BoundedListMergeOperator {
+
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  mut operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        let mut list = match existing_val {
+            Some(val) => try!(read_elements(val)),
+            None => Vec::new()
+        };
+        for operand in operands {
+            list.extend(try!(read_elements(operand)).into_iter());
+        }
+        self.truncate(list)
+    }
+
+    fn partial_merge(&self,
+                     _key: &[u8],
+                     mut operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        // Elements older than the last `max_len` appended elements can never be part of the
+        // result, so the combined operand is truncated as well.
+        let mut list = Vec::new();
+        for operand in operands {
+            list.extend(try!(read_elements(operand)).into_iter());
+        }
+        self.truncate(list)
+    }
+}
+
+/// Maintains a list of distinct byte strings in insertion order.
+///
+/// Appending an element which is already in the list has no effect. The value and operand
+/// encodings are the same as for `BoundedListMergeOperator`.
+pub struct UniqueListMergeOperator;
+
+impl UniqueListMergeOperator {
+
+    /// Create an operand which appends `elements` to the list, skipping those already present.
+    pub fn append(elements: &[&[u8]]) -> io::IoResult<Vec<u8>> {
+        BoundedListMergeOperator::append(elements)
+    }
+
+    /// Read the elements of a list value, in insertion order.
+    pub fn read_list(bytes: &[u8]) -> io::IoResult<Vec<Vec<u8>>> {
+        read_elements(bytes)
+    }
+
+    fn append_unique(list: Vec<Vec<u8>>, mut operands: Operands) -> io::IoResult<Vec<u8>> {
+        let mut list = list;
+        let mut seen: HashSet<Vec<u8>> = list.iter().map(|element| element.clone()).collect();
+        for operand in operands {
+            for element in try!(read_elements(operand)).into_iter() {
+                if seen.insert(element.clone()) {
+                    list.push(element);
+                }
+            }
+        }
+        write_elements(list.iter())
+    }
+}
+
+impl MergeOperator for UniqueListMergeOperator {
+
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        let list = match existing_val {
+            Some(val) => try!(read_elements(val)),
+            None => Vec::new()
+        };
+        UniqueListMergeOperator::append_unique(list, operands)
+    }
+
+    fn partial_merge(&self,
+                     _key: &[u8],
+                     operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        // Keeping the first occurrence of each appended element yields the same result as applying
+        // the operands individually.
+        UniqueListMergeOperator::append_unique(Vec::new(), operands)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+//// Sketch Merge Operators
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+// Operands of the sketch merge operators begin with a tag byte. An item operand (`0`) records raw
+// items into the sketch stored at the key, and a sketch operand (`1`) combines a pre-built sketch
+// with it. Values are always encoded sketches.
+//
+// A partial merge combines the operands into the first sketch operand, so that its parameters are
+// kept. Operands holding only items are not partially merged, since the parameters of the sketch
+// they will be merged into are not known until the full merge.
+
+static SKETCH_ITEM: u8 = 0;
+static SKETCH_SKETCH: u8 = 1;
+
+/// SipHash-2-4 of `bytes` with the key `(k0, k1)`. Sketches are persisted, so an item must hash to
+/// the same value forever; `std::hash` gives no such guarantee, and hashes a length prefix.
+fn sip_hash(k0: u64, k1: u64, bytes: &[u8]) -> u64 {
+    let mut v = [k0 ^ 0x736f6d6570736575, k1 ^ 0x646f72616e646f6d,
+                 k0 ^ 0x6c7967656e657261, k1 ^ 0x7465646279746573];
+    let body_len = bytes.len() - bytes.len() % 8;
+    for word in bytes.slice_to(body_len).chunks(8) {
+        let m = read_le_u64(word);
+        v[3] ^= m;
+        sip_round(&mut v);
+        sip_round(&mut v);
+        v[0] ^= m;
+    }
+    let m = (bytes.len() as u64 << 56) | read_le_u64(bytes.slice_from(body_len));
+    v[3] ^= m;
+    sip_round(&mut v);
+    sip_round(&mut v);
+    v[0] ^= m;
+    v[2] ^= 0xff;
+    for _ in range(0u, 4) {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+fn sip_round(v: &mut [u64, ..4]) {
+    v[0] += v[1]; v[1] = v[1].rotate_left(13); v[1] ^= v[0]; v[0] = v[0].rotate_left(32);
+    v[2] += v[3]; v[3] = v[3].rotate_left(16); v[3] ^= v[2];
+    v[0] += v[3]; v[3] = v[3].rotate_left(21); v[3] ^= v[0];
+    v[2] += v[1]; v[1] = v[1].rotate_left(17); v[1] ^= v[2]; v[2] = v[2].rotate_left(32);
+}
+
+/// Read up to eight bytes as a little-endian integer.
+fn read_le_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().enumerate().fold(0, |word, (i, &byte)| word | (byte as u64 << (8 * i)))
+}
+
+/// A HyperLogLog cardinality estimator with `2^precision` single byte registers.
+///
+/// Encoded as the precision byte followed by the registers.
+#[deriving(Clone, PartialEq, Show)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>
+}
+
+impl HyperLogLog {
+
+    /// Create an empty HyperLogLog. The precision must be between 4 and 16, inclusive. The
+    /// standard error of the estimate is approximately `1.04 / sqrt(2^precision)`.
+    pub fn new(precision: u8) -> HyperLogLog {
+        assert!(precision >= 4 && precision <= 16, "HyperLogLog precision must be in [4, 16]");
+        HyperLogLog { precision: precision,
+                      registers: Vec::from_elem(1u << precision as uint, 0u8) }
+    }
+
+    /// Decode a HyperLogLog, for instance a value read from a column family.
+    pub fn decode(bytes: &[u8]) -> io::IoResult<HyperLogLog> {
+        if bytes.is_empty() {
+            return Err(invalid_operand("empty HyperLogLog"));
+        }
+        let precision = bytes[0];
+        if precision < 4 || precision > 16 || bytes.len() != 1 + (1u << precision as uint) {
+            return Err(invalid_operand("malformed HyperLogLog"));
+        }
+        Ok(HyperLogLog { precision: precision, registers: bytes.slice_from(1).to_vec() })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + self.registers.len());
+        bytes.push(self.precision);
+        bytes.push_all(self.registers.as_slice());
+        bytes
+    }
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Record an item.
+    pub fn insert(&mut self, item: &[u8]) {
+        let hash = sip_hash(0, 0, item);
+        let precision = self.precision as uint;
+        let index = (hash >> (64 - precision)) as uint;
+        let rank = cmp::min((hash << precision).leading_zeros() + 1, 64 - precision + 1) as u8;
+        let registers = self.registers.as_mut_slice();
+        if rank > registers[index] {
+            registers[index] = rank;
+        }
+    }
+
+    /// Combine another HyperLogLog of the same precision into this one.
+    pub fn merge(&mut self, other: &HyperLogLog) -> io::IoResult<()> {
+        if self.precision != other.precision {
+            return Err(invalid_operand("HyperLogLog precision mismatch"));
+        }
+        for (register, &other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if other > *register {
+                *register = other;
+            }
+        }
+        Ok(())
+    }
+
+    /// Estimate the number of distinct items recorded.
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m)
+        };
+
+        let mut sum = 0.0f64;
+        let mut zeros = 0u;
+        for &register in self.registers.iter() {
+            sum += 2.0f64.powi(-(register as i32));
+            if register == 0 {
+                zeros += 1;
+            }
+        }
+
+        let estimate = alpha * m * m / sum;
+        if estimate <= 2.5 * m && zeros > 0 {
+            // Small range correction: fall back to linear counting.
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+/// Maintains a `HyperLogLog` at each key.
+pub struct HyperLogLogMergeOperator {
+    precision: u8
+}
+
+impl HyperLogLogMergeOperator {
+
+    /// Create a HyperLogLog merge operator which creates sketches of the given precision.
+    pub fn new(precision: u8) -> HyperLogLogMergeOperator {
+        assert!(precision >= 4 && precision <= 16, "HyperLogLog precision must be in [4, 16]");
+        HyperLogLogMergeOperator { precision: precision }
+    }
+
+    /// Create an operand which records `item`.
+    pub fn item(item: &[u8]) -> Vec<u8> {
+        let mut operand = Vec::with_capacity(item.len() + 1);
+        operand.push(SKETCH_ITEM);
+        operand.push_all(item);
+        operand
+    }
+
+    /// Create an operand which combines `sketch` with the existing sketch.
+    pub fn sketch(sketch: &HyperLogLog) -> Vec<u8> {
+        let mut operand = vec!(SKETCH_SKETCH);
+        operand.push_all(sketch.encode().as_slice());
+        operand
+    }
+
+    /// Estimate the number of distinct items recorded in a HyperLogLog value.
+    pub fn estimate(value: &[u8]) -> io::IoResult<f64> {
+        HyperLogLog::decode(value).map(|hll| hll.estimate())
+    }
+
+    fn apply(hll: &mut HyperLogLog, operand: &[u8]) -> io::IoResult<()> {
+        if operand.is_empty() {
+            Err(invalid_operand("empty HyperLogLog operand"))
+        } else if operand[0] == SKETCH_ITEM {
+            hll.insert(operand.slice_from(1));
+            Ok(())
+        } else if operand[0] == SKETCH_SKETCH {
+            hll.merge(&try!(HyperLogLog::decode(operand.slice_from(1))))
+        } else {
+            Err(invalid_operand("unknown HyperLogLog operand"))
+        }
+    }
+}
+
+impl MergeOperator for HyperLogLogMergeOperator {
+
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  mut operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        let mut hll = match existing_val {
+            Some(val) => try!(HyperLogLog::decode(val)),
+            None => HyperLogLog::new(self.precision)
+        };
+        for operand in operands {
+            try!(HyperLogLogMergeOperator::apply(&mut hll, operand));
+        }
+        Ok(hll.encode())
+    }
+
+    fn partial_merge(&self,
+                     _key: &[u8],
+                     operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        let operands: Vec<&[u8]> = operands.collect();
+        let first_sketch = operands.iter().find(|operand| operand.get(0) == Some(&SKETCH_SKETCH));
+        let mut hll = match first_sketch {
+            Some(operand) => try!(HyperLogLog::decode(operand.slice_from(1))),
+            None => return Err(invalid_operand("no HyperLogLog operand to merge into"))
+        };
+        hll.registers = Vec::from_elem(hll.registers.len(), 0u8);
+        for &operand in operands.iter() {
+            try!(HyperLogLogMergeOperator::apply(&mut hll, operand));
+        }
+        Ok(HyperLogLogMergeOperator::sketch(&hll))
+    }
+}
+
+/// A count-min sketch frequency estimator with `depth` rows of `width` counters.
+///
+/// Encoded as the big-endian `u32` width and depth, followed by the big-endian `u64` counters in
+/// row-major order. Counters saturate at `u64::MAX`.
+#[deriving(Clone, PartialEq, Show)]
+pub struct CountMinSketch {
+    width: uint,
+    depth: uint,
+    counters: Vec<u64>
+}
+
+impl CountMinSketch {
+
+    /// Create an empty count-min sketch. Estimates exceed the true count by at most
+    /// `e / width * total` with probability `1 - exp(-depth)`.
+    pub fn new(width: uint, depth: uint) -> CountMinSketch {
+        assert!(width > 0 && depth > 0, "count-min sketch dimensions must be positive");
+        CountMinSketch { width: width, depth: depth, counters: Vec::from_elem(width * depth, 0u64) }
+    }
+
+    /// Decode a count-min sketch, for instance a value read from a column family.
+    pub fn decode(bytes: &[u8]) -> io::IoResult<CountMinSketch> {
+        let mut reader = io::BufReader::new(bytes);
+        let width = try!(reader.read_be_u32()) as uint;
+        let depth = try!(reader.read_be_u32()) as uint;
+        // Bound the dimensions before multiplying so that malformed input can not overflow.
+        if width == 0 || depth == 0 || depth > (uint::MAX - 8) / 8 / width
+            || bytes.len() != 8 + width * depth * 8 {
+            return Err(invalid_operand("malformed count-min sketch"));
+        }
+        let mut counters = Vec::with_capacity(width * depth);
+        for _ in range(0, width * depth) {
+            counters.push(try!(reader.read_be_u64()));
+        }
+        Ok(CountMinSketch { width: width, depth: depth, counters: counters })
+    }
+
+    pub fn encode(&self) -> io::IoResult<Vec<u8>> {
+        let mut writer = io::MemWriter::with_capacity(8 + self.counters.len() * 8);
+        try!(writer.write_be_u32(self.width as u32));
+        try!(writer.write_be_u32(self.depth as u32));
+        for &counter in self.counters.iter() {
+            try!(writer.write_be_u64(counter));
+        }
+        Ok(writer.unwrap())
+    }
+
+    pub fn width(&self) -> uint {
+        self.width
+    }
+
+    pub fn depth(&self) -> uint {
+        self.depth
+    }
+
+    /// Record `count` occurrences of an item.
+    pub fn insert(&mut self, item: &[u8], count: u64) {
+        for row in range(0, self.depth) {
+            let index = self.index(row, item);
+            let counter = &mut self.counters.as_mut_slice()[index];
+            *counter = counter.saturating_add(count);
+        }
+    }
+
+    /// Combine another count-min sketch of the same dimensions into this one.
+    pub fn merge(&mut self, other: &CountMinSketch) -> io::IoResult<()> {
+        if self.width != other.width || self.depth != other.depth {
+            return Err(invalid_operand("count-min sketch dimension mismatch"));
+        }
+        for (counter, &other) in self.counters.iter_mut().zip(other.counters.iter()) {
+            *counter = counter.saturating_add(other);
+        }
+        Ok(())
+    }
+
+    /// Estimate the number of occurrences of an item. The estimate is never less than the true
+    /// count.
+    pub fn estimate(&self, item: &[u8]) -> u64 {
+        range(0, self.depth).map(|row| self.counters[self.index(row, item)])
+                            .min()
+                            .unwrap_or(0)
+    }
+
+    fn index(&self, row: uint, item: &[u8]) -> uint {
+        row * self.width + (sip_hash(row as u64, 0, item) % self.width as u64) as uint
+    }
+}
+
+/// Maintains a `CountMinSketch` at each key.
+pub struct CountMinMergeOperator {
+    width: uint,
+    depth: uint
+}
+
+impl CountMinMergeOperator {
+
+    /// Create a count-min merge operator which creates sketches of the given dimensions.
+    pub fn new(width: uint, depth: uint) -> CountMinMergeOperator {
+        assert!(width > 0 && depth > 0, "count-min sketch dimensions must be positive");
+        CountMinMergeOperator { width: width, depth: depth }
+    }
+
+    /// Create an operand which records `count` occurrences of `item`.
+    ///
+    /// Encoded as the tag byte, the big-endian `u64` count, and the item.
+    pub fn item(item: &[u8], count: u64) -> io::IoResult<Vec<u8>> {
+        let mut writer = io::MemWriter::with_capacity(item.len() + 9);
+        try!(writer.write_u8(SKETCH_ITEM));
+        try!(writer.write_be_u64(count));
+        try!(writer.write(item));
+        Ok(writer.unwrap())
+    }
+
+    /// Create an operand which combines `sketch` with the existing sketch.
+    pub fn sketch(sketch: &CountMinSketch) -> io::IoResult<Vec<u8>> {
+        let mut operand = vec!(SKETCH_SKETCH);
+        operand.push_all(try!(sketch.encode()).as_slice());
+        Ok(operand)
+    }
+
+    /// Estimate the number of occurrences of `item` recorded in a count-min sketch value.
+    pub fn estimate(value: &[u8], item: &[u8]) -> io::IoResult<u64> {
+        CountMinSketch::decode(value).map(|cms| cms.estimate(item))
+    }
+
+    fn apply(cms: &mut CountMinSketch, operand: &[u8]) -> io::IoResult<()> {
+        if operand.is_empty() {
+            Err(invalid_operand("empty count-min operand"))
+        } else if operand[0] == SKETCH_ITEM {
+            let count = try!(io::BufReader::new(operand.slice(1, cmp::min(9, operand.len())))
+                                           .read_be_u64());
+            cms.insert(operand.slice_from(9), count);
+            Ok(())
+        } else if operand[0] == SKETCH_SKETCH {
+            cms.merge(&try!(CountMinSketch::decode(operand.slice_from(1))))
+        } else {
+            Err(invalid_operand("unknown count-min operand"))
+        }
+    }
+}
+
+impl MergeOperator for CountMinMergeOperator {
+
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  mut operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        let mut cms = match existing_val {
+            Some(val) => try!(CountMinSketch::decode(val)),
+            None => CountMinSketch::new(self.width, self.depth)
+        };
+        for operand in operands {
+            try!(CountMinMergeOperator::apply(&mut cms, operand));
+        }
+        cms.encode()
+    }
+
+    fn partial_merge(&self,
+                     _key: &[u8],
+                     operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        let operands: Vec<&[u8]> = operands.collect();
+        let first_sketch = operands.iter().find(|operand| operand.get(0) == Some(&SKETCH_SKETCH));
+        let mut cms = match first_sketch {
+            Some(operand) => {
+                let sketch = try!(CountMinSketch::decode(operand.slice_from(1)));
+                CountMinSketch::new(sketch.width, sketch.depth)
+            },
+            None => return Err(invalid_operand("no count-min operand to merge into"))
+        };
+        for &operand in operands.iter() {
+            try!(CountMinMergeOperator::apply(&mut cms, operand));
+        }
+        CountMinMergeOperator::sketch(&cms)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+//// JSON Merge Operator
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Marks an operand holding a JSON list of merge patches which could not be composed into a single
+/// patch. JSON text never begins with a null byte, so these can not be confused with a patch.
+static JSON_PATCH_SEQUENCE: u8 = 0;
+
+fn read_json(bytes: &[u8]) -> io::IoResult<json::Json> {
+    let text = try!(str::from_utf8(bytes).ok_or(invalid_operand("JSON document is not UTF-8")));
+    json::from_str(text).map_err(|_| invalid_operand("malformed JSON document"))
+}
+
+/// Read a JSON merge operand as the sequence of patches it contains.
+fn read_json_patches(operand: &[u8]) -> io::IoResult<Vec<json::Json>> {
+    if !operand.is_empty() && operand[0] == JSON_PATCH_SEQUENCE {
+        match try!(read_json(operand.slice_from(1))) {
+            json::List(patches) => Ok(patches),
+            _ => Err(invalid_operand("malformed JSON merge patch sequence"))
+        }
+    } else {
+        Ok(vec!(try!(read_json(operand))))
+    }
+}
+
+/// Apply a merge patch to a target document as specified by RFC 7396.
+fn apply_merge_patch(target: json::Json, patch: &json::Json) -> json::Json {
+    match *patch {
+        json::Object(ref patch) => {
+            let mut target = match target {
+                json::Object(target) => target,
+                _ => TreeMap::new()
+            };
+            for (name, value) in patch.iter() {
+                let existing = target.pop(name);
+                if *value != json::Null {
+                    target.insert(name.clone(),
+                                  apply_merge_patch(existing.unwrap_or(json::Null), value));
+                }
+            }
+            json::Object(target)
+        },
+        _ => patch.clone()
+    }
+}
+
+/// Compose two merge patches into a single patch which has the same effect as applying `first`
+/// followed by `second`. Returns `None` when no such patch exists, which is the case when `second`
+/// patches an object into a member that `first` replaces with a non-object or removes.
+fn compose_merge_patches(first: &json::Json, second: &json::Json) -> Option<json::Json> {
+    match (first, second) {
+        (&json::Object(ref first), &json::Object(ref second)) => {
+            let mut composed = first.clone();
+            for (name, value) in second.iter() {
+                let value = match composed.pop(name) {
+                    Some(existing) => match compose_merge_patches(&existing, value) {
+                        Some(value) => value,
+                        None => return None
+                    },
+                    None => value.clone()
+                };
+                composed.insert(name.clone(), value);
+            }
+            Some(json::Object(composed))
+        },
+        (_, &json::Object(_)) => None,
+        _ => Some(second.clone())
+    }
+}
+
+/// Maintains a JSON document at each key, updated with JSON Merge Patch (RFC 7396) operands.
+///
+/// Each operand is a UTF-8 encoded merge patch: members of a patch object replace the
+/// corresponding members of the document, nested objects are patched recursively, and `null`
+/// members are removed. A patch which is not an object replaces the whole document. If the key has
+/// no value, the patch is applied to an empty document.
+///
+/// Partial merges compose adjacent patches. Patches which can not be composed are kept as a
+/// sequence in an operand consisting of a null byte followed by a JSON list of the patches.
+pub struct JsonMergeOperator;
+
+impl JsonMergeOperator {
+
+    /// Create an operand which applies `patch` to the document.
+    pub fn patch(patch: &json::Json) -> Vec<u8> {
+        patch.to_string().into_bytes()
+    }
+
+    /// Read a JSON document value.
+    pub fn read_document(bytes: &[u8]) -> io::IoResult<json::Json> {
+        read_json(bytes)
+    }
+}
+
+impl MergeOperator for JsonMergeOperator {
+
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  mut operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        let mut document = match existing_val {
+            Some(val) => try!(read_json(val)),
+            None => json::Null
+        };
+        for operand in operands {
+            for patch in try!(read_json_patches(operand)).iter() {
+                document = apply_merge_patch(document, patch);
+            }
+        }
+        Ok(document.to_string().into_bytes())
+    }
+
+    fn partial_merge(&self,
+                     _key: &[u8],
+                     mut operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        let mut patches: Vec<json::Json> = Vec::new();
+        for operand in operands {
+            for patch in try!(read_json_patches(operand)).into_iter() {
+                let composed = patches.last().and_then(|last| compose_merge_patches(last, &patch));
+                match composed {
+                    Some(composed) => { patches.pop(); patches.push(composed); },
+                    None => patches.push(patch)
+                }
+            }
+        }
+
+        if patches.len() == 1 {
+            Ok(patches.pop().unwrap().to_string().into_bytes())
+        } else {
+            let mut operand = vec!(JSON_PATCH_SEQUENCE);
+            operand.push_all(json::List(patches).to_string().as_bytes());
+            Ok(operand)
+        }
+    }
+}
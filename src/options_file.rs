@@ -0,0 +1,153 @@
+//! Reading and writing database and column family options as text.
+//!
+//! Options are stored in a text format modelled on the section layout of RocksDB options files:
+//!
+//! ```ignore
+//! [DBOptions]
+//!   max_open_files=-1
+//!   wal_dir=/var/lib/wal
+//!
+//! [CFOptions "default"]
+//!   write_buffer_size=67108864
+//!   max_bytes_for_level_multiplier_additional=1:1:2
+//!   merge_operator=add
+//! ```
+//!
+//! Blank lines and lines starting with `#` are ignored. Only options which have been set are
+//! written, and options missing from the text keep the RocksDB defaults when read. Options are
+//! applied in the order they appear, so explicit settings following an `optimize_*` helper override
+//! the helper. Comparators, merge operators and prefix extractors are written by name for
+//! reference, but are skipped when reading and must be set in code. Quotes, backslashes and line
+//! breaks in column family names are escaped as `\"`, `\\`, `\n` and `\r`.
+//!
+//! The format is not interchangeable with the OPTIONS files written by RocksDB. Most options use
+//! the RocksDB option names, but helpers such as `increase_parallelism`, `prepare_for_bulkload`
+//! and the `optimize_*` methods are recorded under their own names, which RocksDB does not
+//! recognize, and RocksDB options without a setter in this crate are rejected when read.
+
+use std::collections::HashMap;
+use std::io;
+
+use super::{ColumnFamilyOptions, DatabaseOptions};
+
+/// Write database options and column family options in the options file format. Column families
+/// are written in order of name.
+pub fn write_options<W: Writer>(writer: &mut W,
+                                db_options: &DatabaseOptions,
+                                cf_options: &HashMap<String, ColumnFamilyOptions>)
+                                -> io::IoResult<()> {
+    try!(writeln!(writer, "[DBOptions]"));
+    for &(ref name, ref value) in db_options.settings().iter() {
+        try!(writeln!(writer, "  {}={}", name, value));
+    }
+
+    let mut column_families: Vec<(&String, &ColumnFamilyOptions)> = cf_options.iter().collect();
+    column_families.sort_by(|a, b| a.val0().cmp(b.val0()));
+    for &(cf_name, options) in column_families.iter() {
+        try!(writeln!(writer, "\n[CFOptions \"{}\"]", escape_name(cf_name.as_slice())));
+        for &(ref name, ref value) in options.settings().iter() {
+            try!(writeln!(writer, "  {}={}", name, value));
+        }
+    }
+    Ok(())
+}
+
+/// Read database options and column family options from text in the options file format.
+pub fn read_options(text: &str)
+                    -> Result<(DatabaseOptions, HashMap<String, ColumnFamilyOptions>), String> {
+    let mut db_options = DatabaseOptions::new();
+    let mut cf_options: HashMap<String, ColumnFamilyOptions> = HashMap::new();
+
+    // `None` before the first section, `Some(None)` in the database options section, and
+    // `Some(Some(name))` in the options section of column family `name`.
+    let mut section: Option<Option<String>> = None;
+
+    for (line_num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue;
+        }
+
+        if line.starts_with("[") && line.ends_with("]") {
+            let header = line.slice(1, line.len() - 1).trim();
+            if header == "DBOptions" {
+                section = Some(None);
+            } else if header.starts_with("CFOptions") {
+                let name = header.slice_from(9).trim();
+                if name.len() < 2 || !name.starts_with("\"") || !name.ends_with("\"") {
+                    return Err(format!("line {}: malformed column family section: {}",
+                                       line_num + 1, line));
+                }
+                let name = match unescape_name(name.slice(1, name.len() - 1)) {
+                    Some(name) => name,
+                    None => return Err(format!("line {}: malformed column family name: {}",
+                                               line_num + 1, line))
+                };
+                if cf_options.contains_key(&name) {
+                    return Err(format!("line {}: duplicate column family section: {}",
+                                       line_num + 1, name));
+                }
+                cf_options.insert(name.clone(), ColumnFamilyOptions::new());
+                section = Some(Some(name));
+            } else {
+                return Err(format!("line {}: unknown section: {}", line_num + 1, line));
+            }
+            continue;
+        }
+
+        let (name, value) = match line.find('=') {
+            Some(idx) => (line.slice_to(idx).trim(), line.slice_from(idx + 1).trim()),
+            None => return Err(format!("line {}: expected name=value: {}", line_num + 1, line))
+        };
+
+        let result = match section {
+            None => Err("option outside of a section".to_string()),
+            Some(None) => db_options.set(name, value).map(|_| ()),
+            Some(Some(ref cf_name)) => {
+                if name == "comparator" || name == "merge_operator" || name == "prefix_extractor" {
+                    Ok(())
+                } else {
+                    cf_options.find_mut(cf_name).unwrap().set(name, value).map(|_| ())
+                }
+            }
+        };
+        try!(result.map_err(|error| format!("line {}: {}", line_num + 1, error)));
+    }
+
+    Ok((db_options, cf_options))
+}
+
+/// Escape the quotes, backslashes and line breaks in a column family name.
+fn escape_name(name: &str) -> String {
+    let mut escaped = String::new();
+    for c in name.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c)
+        }
+    }
+    escaped
+}
+
+/// Reverse `escape_name`, returning `None` for an unknown escape or an unescaped quote.
+fn unescape_name(escaped: &str) -> Option<String> {
+    let mut name = String::new();
+    let mut chars = escaped.chars();
+    loop {
+        match chars.next() {
+            None => return Some(name),
+            Some('\\') => match chars.next() {
+                Some('"') => name.push('"'),
+                Some('\\') => name.push('\\'),
+                Some('n') => name.push('\n'),
+                Some('r') => name.push('\r'),
+                _ => return None
+            },
+            Some('"') => return None,
+            Some(c) => name.push(c)
+        }
+    }
+}
@@ -1,6 +1,19 @@
 use super::*;
-use super::merge_operators::{AddMergeOperator, ConcatMergeOperator};
+use super::backup::BackupEngine;
+use super::info_log;
+use super::options_file;
+use super::merge_operators::{AddMergeOperator, BoundedListMergeOperator, ConcatMergeOperator,
+                             CountMinMergeOperator, CountMinSketch, HyperLogLog,
+                             HyperLogLogMergeOperator, JsonMergeOperator, SetMergeOperator,
+                             UniqueListMergeOperator};
+use log;
+use serialize::json;
+use std::collections::HashMap;
 use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use std::sync::atomic::{AtomicUint, SeqCst};
+use std::{u64, uint};
 
 #[test]
 fn test_create_database() {
@@ -61,6 +74,494 @@ fn test_put_get() {
     assert_eq!(other.get(&read_options, b"key").unwrap().unwrap().as_slice(), b"val");
 }
 
+#[test]
+fn test_flush() {
+    let dir = io::TempDir::new("").unwrap();
+    let table_files = || {
+        io::fs::readdir(dir.path()).unwrap()
+                                   .iter()
+                                   .filter(|path| path.extension_str() == Some("sst"))
+                                   .count()
+    };
+
+    {
+        let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                       ("other".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+        let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+        let default = db.get_column_family("default").unwrap();
+        let other = db.get_column_family("other").unwrap();
+        let mut write_options = WriteOptions::new();
+        write_options.set_write_to_wal(false);
+
+        default.put(&write_options, b"key", b"val").unwrap();
+        other.put(&write_options, b"key", b"val").unwrap();
+        assert_eq!(0, table_files());
+
+        default.flush(&FlushOptions::new()).unwrap();
+        assert_eq!(1, table_files());
+
+        other.put(&write_options, b"key2", b"val2").unwrap();
+        db.flush(&FlushOptions::new()).unwrap();
+        assert_eq!(2, table_files());
+    }
+
+    // Writes which skipped the write ahead log survive a reopen, because they were flushed.
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("other".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::open(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let read_options = ReadOptions::new();
+    let default = db.get_column_family("default").unwrap();
+    let other = db.get_column_family("other").unwrap();
+    assert_eq!(b"val", default.get(&read_options, b"key").unwrap().unwrap().as_slice());
+    assert_eq!(b"val", other.get(&read_options, b"key").unwrap().unwrap().as_slice());
+    assert_eq!(b"val2", other.get(&read_options, b"key2").unwrap().unwrap().as_slice());
+}
+
+#[test]
+fn test_compact_range() {
+    let dir = io::TempDir::new("").unwrap();
+    let table_files_size = || {
+        io::fs::readdir(dir.path()).unwrap()
+                                   .iter()
+                                   .filter(|path| path.extension_str() == Some("sst"))
+                                   .map(|path| io::fs::stat(path).unwrap().size)
+                                   .fold(0, |a, b| a + b)
+    };
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("other".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    let other = db.get_column_family("other").unwrap();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let val = Vec::from_elem(1024, 0xABu8);
+    for i in range(0u, 1000) {
+        let key = format!("{:08}", i);
+        default.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+        other.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+    }
+    db.flush(&FlushOptions::new()).unwrap();
+    for i in range(0u, 900) {
+        let key = format!("{:08}", i);
+        default.delete(write_options, key.as_bytes()).unwrap();
+        other.delete(write_options, key.as_bytes()).unwrap();
+    }
+    db.flush(&FlushOptions::new()).unwrap();
+
+    // Compacting part of one column family reclaims some space.
+    let size = table_files_size();
+    default.compact_range(None, Some(b"00000500"));
+    assert!(table_files_size() < size);
+
+    // Compacting the rest on a background task reclaims more.
+    let size = table_files_size();
+    db.compact_range_in_background(None, None).wait();
+    assert!(table_files_size() < size);
+
+    // Dropping a handle waits for the compaction.
+    {
+        let mut handle = other.compact_range_in_background(Some(b"00000900"), None);
+        handle.is_complete();
+    }
+
+    assert_eq!(100, default.iter(read_options).unwrap().count());
+    assert_eq!(100, other.iter(read_options).unwrap().count());
+}
+
+#[test]
+fn test_properties() {
+    let dir = io::TempDir::new("").unwrap();
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("other".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    let other = db.get_column_family("other").unwrap();
+    let write_options = &WriteOptions::new();
+
+    for i in range(0u, 100) {
+        let key = format!("{:08}", i);
+        other.put(write_options, key.as_bytes(), key.as_bytes()).unwrap();
+    }
+
+    assert!(db.property("rocksdb.no-such-property").is_none());
+    assert!(db.property("rocksdb.stats").is_some());
+    assert!(other.stats().is_some());
+    assert_eq!(Some(0), default.estimate_num_keys());
+    assert_eq!(Some(100), other.estimate_num_keys());
+    assert_eq!(Some(100), other.num_entries_active_mem_table());
+    assert!(other.cur_size_active_mem_table().unwrap() > 0);
+    assert!(other.cur_size_all_mem_tables().unwrap() >= other.cur_size_active_mem_table().unwrap());
+    assert_eq!(Some(0), other.num_immutable_mem_table());
+    assert_eq!(Some(false), other.mem_table_flush_pending());
+    assert_eq!(Some(0), other.num_files_at_level(0));
+    assert_eq!(Some(0), other.background_errors());
+
+    other.flush(&FlushOptions::new()).unwrap();
+    assert_eq!(Some(0), other.num_entries_active_mem_table());
+    assert_eq!(Some(1), other.num_files_at_level(0));
+    assert!(other.compaction_pending().is_some());
+    assert!(other.sstables().unwrap().as_slice().contains(".sst"));
+
+    let level_stats = other.level_stats().unwrap();
+    assert_eq!(LevelStats { level: 0, num_files: 1, size_mb: 0 }, level_stats[0]);
+    assert!(level_stats.iter().skip(1).all(|level| level.num_files == 0));
+}
+
+#[test]
+fn test_statistics() {
+    let dir = io::TempDir::new("").unwrap();
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    assert!(db.statistics().is_none());
+    drop(db);
+
+    let dir = io::TempDir::new("").unwrap();
+    let mut db_options = DatabaseOptions::new();
+    db_options.enable_statistics();
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(dir.path(), db_options, cfs).unwrap();
+    let cf = db.get_column_family("default").unwrap();
+
+    for i in range(0u, 100) {
+        let key = format!("{:08}", i);
+        cf.put(&WriteOptions::new(), key.as_bytes(), key.as_bytes()).unwrap();
+    }
+    db.flush(&FlushOptions::new()).unwrap();
+    for i in range(0u, 100) {
+        let key = format!("{:08}", i);
+        assert!(cf.get(&ReadOptions::new(), key.as_bytes()).unwrap().is_some());
+    }
+
+    let statistics = db.statistics().unwrap();
+    assert_eq!(Some(100), statistics.ticker(TickerNumberKeysWritten));
+    assert_eq!(Some(&100), statistics.tickers().find_equiv("rocksdb.number.keys.written"));
+    assert!(statistics.bytes_written() >= 1600);
+    assert_eq!(800, statistics.bytes_read());
+    assert!(statistics.block_cache_hits() + statistics.block_cache_misses() > 0);
+    let get_micros = statistics.get_micros().unwrap();
+    assert!(get_micros.median <= get_micros.percentile95);
+    assert!(get_micros.percentile95 <= get_micros.percentile99);
+    assert!(statistics.write_micros().is_some());
+}
+
+#[test]
+fn test_statistics_parse() {
+    // Histograms in the format of older versions of RocksDB, and an unknown line.
+    let statistics = Statistics::parse("rocksdb.block.cache.miss COUNT : 3\n\
+                                        rocksdb.block.cache.hit COUNT : 5\n\
+                                        rocksdb.some.future.line 1 2 3\n\
+                                        rocksdb.db.get.micros statistics Percentiles :=> \
+                                        50 : 1.5 95 : 2.5 99 : 3.5\n");
+    assert_eq!(Some(3), statistics.ticker(TickerBlockCacheMiss));
+    assert_eq!(5, statistics.block_cache_hits());
+    assert_eq!(None, statistics.ticker(TickerBytesRead));
+    assert_eq!(2, statistics.tickers().len());
+    assert_eq!(Some(Histogram { median: 1.5,
+                                percentile95: 2.5,
+                                percentile99: 3.5,
+                                count: None,
+                                sum: None }),
+               statistics.get_micros());
+    assert_eq!(1, statistics.histograms().len());
+
+    // Histograms in the format of newer versions of RocksDB.
+    let statistics = Statistics::parse("rocksdb.bytes.read COUNT : 800\n\
+                                        rocksdb.db.write.micros P50 : 4.000000 P95 : 8.500000 \
+                                        P99 : 9.000000 P100 : 12.000000 COUNT : 100 SUM : 520\n");
+    assert_eq!(800, statistics.bytes_read());
+    assert_eq!(Some(Histogram { median: 4.0,
+                                percentile95: 8.5,
+                                percentile99: 9.0,
+                                count: Some(100),
+                                sum: Some(520) }),
+               statistics.histogram(HistogramDbWrite));
+    assert_eq!(None, statistics.get_micros());
+}
+
+#[test]
+fn test_approximate_sizes() {
+    let dir = io::TempDir::new("").unwrap();
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    let write_options = &WriteOptions::new();
+
+    // Values are pseudo-random so that they do not compress.
+    let mut state = 1u32;
+    for i in range(0u, 1000) {
+        let key = format!("{:08}", i);
+        let val: Vec<u8> = range(0u, 1024).map(|_| {
+            state = state * 1103515245 + 12345;
+            (state >> 16) as u8
+        }).collect();
+        default.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+    }
+    default.flush(&FlushOptions::new()).unwrap();
+
+    let sizes = default.approximate_sizes(&[(b"00000000", b"00001000"),
+                                            (b"00000000", b"00000500"),
+                                            (b"00002000", b"00003000")]);
+    assert_eq!(3, sizes.len());
+    assert!(sizes[0] >= 900 * 1024);
+    assert!(sizes[1] > sizes[0] / 4 && sizes[1] < sizes[0] * 3 / 4);
+    assert_eq!(0, sizes[2]);
+    assert!(default.approximate_sizes(&[]).is_empty());
+}
+
+#[test]
+fn test_live_files() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_disable_auto_compactions(true);
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("other".to_string(), cf_options)).into_iter().collect();
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let other = db.get_column_family("other").unwrap();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+    assert!(db.live_files().is_empty());
+
+    for i in range(0u, 10) {
+        other.put(write_options, format!("a{}", i).as_bytes(), b"val").unwrap();
+    }
+    other.flush(&FlushOptions::new()).unwrap();
+    for i in range(0u, 10) {
+        other.put(write_options, format!("b{}", i).as_bytes(), b"val").unwrap();
+    }
+    other.flush(&FlushOptions::new()).unwrap();
+
+    let mut files = db.live_files();
+    files.sort_by(|a, b| a.smallest_key.cmp(&b.smallest_key));
+    assert_eq!(2, files.len());
+    for file in files.iter() {
+        assert_eq!("other", file.column_family.as_slice());
+        assert!(file.name.as_slice().ends_with(".sst"));
+        assert_eq!(0, file.level);
+        assert!(file.size > 0);
+    }
+    assert_eq!(b"a0", files[0].smallest_key.as_slice());
+    assert_eq!(b"a9", files[0].largest_key.as_slice());
+    assert_eq!(b"b0", files[1].smallest_key.as_slice());
+    assert_eq!(b"b9", files[1].largest_key.as_slice());
+
+    // Level 0 files are never deleted.
+    assert!(db.delete_file(&files[0], b"a", b"z").is_err());
+
+    // Compacting moves the keys into a single file below level 0, which is only deleted when
+    // its keys are within the obsolete range.
+    other.compact_range(None, None);
+    let files = db.live_files();
+    assert_eq!(1, files.len());
+    assert!(files[0].level > 0);
+    assert!(db.delete_file(&files[0], b"a", b"b5").is_err());
+    assert!(db.delete_file(&files[0], b"a1", b"z").is_err());
+    db.delete_file(&files[0], b"a", b"z").unwrap();
+    assert!(other.get(read_options, b"a0").unwrap().is_none());
+    assert!(other.get(read_options, b"b9").unwrap().is_none());
+    assert!(db.live_files().is_empty());
+
+    // Deleting a file which is no longer live fails.
+    assert!(db.delete_file(&files[0], b"a", b"z").is_err());
+}
+
+/// Get every key and value in a column family.
+fn contents(cf: &ColumnFamily) -> Vec<(Vec<u8>, Vec<u8>)> {
+    cf.iter(&ReadOptions::new()).unwrap().map(|kv| (kv.key, kv.value)).collect()
+}
+
+#[test]
+fn test_backup() {
+    let db_dir = io::TempDir::new("").unwrap();
+    let backup_dir = io::TempDir::new("").unwrap();
+    let restore_dir = io::TempDir::new("").unwrap();
+    let write_options = &WriteOptions::new();
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(db_dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    let engine = BackupEngine::open(backup_dir.path()).unwrap();
+
+    for i in range(0u, 100) {
+        default.put(write_options, format!("{:08}", i).as_bytes(), b"v1").unwrap();
+    }
+    assert_eq!(1, engine.create_backup(&db, true).unwrap());
+    let contents1 = contents(default);
+
+    // The second backup is taken without a flush, so its changes are only in the logs.
+    for i in range(50u, 150) {
+        default.put(write_options, format!("{:08}", i).as_bytes(), b"v2").unwrap();
+    }
+    assert_eq!(2, engine.create_backup(&db, false).unwrap());
+    let contents2 = contents(default);
+
+    let backups = engine.backups().unwrap();
+    assert_eq!(vec!(1, 2), backups.iter().map(|backup| backup.id).collect::<Vec<u32>>());
+    engine.verify_backup(1).unwrap();
+    engine.verify_backup(2).unwrap();
+
+    let restore = |id: u32, name: &str, expected: &Vec<(Vec<u8>, Vec<u8>)>| {
+        let path = restore_dir.path().join(name);
+        engine.restore_backup(id, &path).unwrap();
+        let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+        let restored = Database::open(&path, DatabaseOptions::new(), cfs).unwrap();
+        assert_eq!(*expected, contents(restored.get_column_family("default").unwrap()));
+    };
+    restore(1, "1", &contents1);
+    restore(2, "2", &contents2);
+    assert!(engine.restore_backup(2, &restore_dir.path().join("2")).is_err());
+
+    // Purging the first backup leaves the second intact.
+    engine.purge_old_backups(1).unwrap();
+    assert_eq!(vec!(2), engine.backups().unwrap().iter().map(|b| b.id).collect::<Vec<u32>>());
+    engine.verify_backup(2).unwrap();
+    restore(2, "3", &contents2);
+
+    engine.delete_backup(2).unwrap();
+    assert!(engine.backups().unwrap().is_empty());
+    assert!(engine.restore_latest_backup(&restore_dir.path().join("4")).is_err());
+}
+
+#[test]
+fn test_backup_during_writes() {
+    let db_dir = io::TempDir::new("").unwrap();
+    let backup_dir = io::TempDir::new("").unwrap();
+    let restore_dir = io::TempDir::new("").unwrap();
+    let write_options = &WriteOptions::new();
+
+    // A small write buffer makes the writes flush memtables on background threads, so table
+    // files are being written while the backups are taken.
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_write_buffer_size(64 << 10);
+    let cfs = vec!(("default".to_string(), cf_options)).into_iter().collect();
+    let db = Database::create(db_dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    let engine = BackupEngine::open(backup_dir.path()).unwrap();
+    let value = Vec::from_elem(1 << 10, b'v');
+
+    for i in range(0u, 1000) {
+        default.put(write_options, format!("{:08}", i).as_bytes(), value.as_slice()).unwrap();
+    }
+
+    // Compactions rewrite table files concurrently with the remaining writes and backups.
+    let _compaction = db.compact_range_in_background(None, None);
+    let mut expected = Vec::new();
+    for round in range(0u, 10) {
+        for i in range(round * 200, round * 200 + 500) {
+            default.put(write_options, format!("{:08}", i).as_bytes(), value.as_slice()).unwrap();
+        }
+        let id = engine.create_backup(&db, round % 2 == 0).unwrap();
+        expected.push((id, contents(default)));
+    }
+
+    for &(id, ref contents_at_backup) in expected.iter() {
+        engine.verify_backup(id).unwrap();
+        let path = restore_dir.path().join(id.to_string());
+        engine.restore_backup(id, &path).unwrap();
+        let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+        let restored = Database::open(&path, DatabaseOptions::new(), cfs).unwrap();
+        assert_eq!(*contents_at_backup, contents(restored.get_column_family("default").unwrap()));
+    }
+}
+
+#[test]
+fn test_backup_verify_corruption() {
+    let db_dir = io::TempDir::new("").unwrap();
+    let backup_dir = io::TempDir::new("").unwrap();
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(db_dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    default.put(&WriteOptions::new(), b"key", b"val").unwrap();
+
+    let mut engine = BackupEngine::open(backup_dir.path()).unwrap();
+    engine.set_hard_link(false);
+    let id = engine.create_backup(&db, true).unwrap();
+    engine.verify_backup(id).unwrap();
+
+    let shared = io::fs::readdir(&backup_dir.path().join("shared")).unwrap();
+    assert_eq!(1, shared.len());
+    io::File::open_mode(&shared[0], io::Append, io::Write).unwrap().write(b"garbage").unwrap();
+    assert!(engine.verify_backup(id).is_err());
+    assert!(engine.restore_backup(id, &backup_dir.path().join("restore")).is_err());
+}
+
+#[test]
+fn test_backup_recreated_database() {
+    let backup_dir = io::TempDir::new("").unwrap();
+    let restore_dir = io::TempDir::new("").unwrap();
+    let engine = BackupEngine::open(backup_dir.path()).unwrap();
+
+    // Both databases write a single table file with the same name and size.
+    for (i, value) in [b"v1", b"v2"].iter().enumerate() {
+        let db_dir = io::TempDir::new("").unwrap();
+        let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+        let db = Database::create(db_dir.path(), DatabaseOptions::new(), cfs).unwrap();
+        db.get_column_family("default").unwrap().put(&WriteOptions::new(), b"key", *value).unwrap();
+        assert_eq!(i as u32 + 1, engine.create_backup(&db, true).unwrap());
+    }
+    assert_eq!(2, io::fs::readdir(&backup_dir.path().join("shared")).unwrap().len());
+
+    for (i, value) in [b"v1", b"v2"].iter().enumerate() {
+        let path = restore_dir.path().join(i.to_string());
+        engine.restore_backup(i as u32 + 1, &path).unwrap();
+        let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+        let db = Database::open(&path, DatabaseOptions::new(), cfs).unwrap();
+        let default = db.get_column_family("default").unwrap();
+        assert_eq!(vec!((b"key".to_vec(), value.to_vec())), contents(default));
+    }
+}
+
+#[test]
+fn test_checkpoint() {
+    let db_dir = io::TempDir::new("").unwrap();
+    let checkpoint_dir = io::TempDir::new("").unwrap();
+    let checkpoint_path = checkpoint_dir.path().join("checkpoint");
+    let write_options = &WriteOptions::new();
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("other".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(db_dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    let other = db.get_column_family("other").unwrap();
+
+    for i in range(0u, 100) {
+        let key = format!("{:08}", i);
+        default.put(write_options, key.as_bytes(), b"v1").unwrap();
+        other.put(write_options, key.as_bytes(), b"v1").unwrap();
+    }
+    // A staging directory left behind by an interrupted checkpoint is replaced.
+    let staging_path = checkpoint_dir.path().join("checkpoint.tmp");
+    io::fs::mkdir(&staging_path, io::USER_RWX).unwrap();
+    io::File::create(&staging_path.join("CURRENT")).unwrap();
+    db.checkpoint(&checkpoint_path).unwrap();
+    assert!(io::fs::stat(&staging_path).is_err());
+    let default_contents = contents(default);
+    let other_contents = contents(other);
+    assert!(db.checkpoint(&checkpoint_path).is_err());
+
+    // Later writes to the database do not affect the checkpoint.
+    for i in range(0u, 200) {
+        let key = format!("{:08}", i);
+        default.put(write_options, key.as_bytes(), b"v2").unwrap();
+        other.delete(write_options, key.as_bytes()).unwrap();
+    }
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("other".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let copy = Database::open(&checkpoint_path, DatabaseOptions::new(), cfs).unwrap();
+    assert_eq!(default_contents, contents(copy.get_column_family("default").unwrap()));
+    assert_eq!(other_contents, contents(copy.get_column_family("other").unwrap()));
+
+    // Nor do writes to the checkpoint affect the database.
+    let copy_default = copy.get_column_family("default").unwrap();
+    copy_default.put(write_options, b"checkpoint", b"val").unwrap();
+    assert!(default.get(&ReadOptions::new(), b"checkpoint").unwrap().is_none());
+    assert_eq!(200, contents(default).len());
+    assert_eq!(0, contents(other).len());
+}
+
 #[test]
 fn test_iterator() {
     let dir = io::TempDir::new("").unwrap();
@@ -89,30 +590,696 @@ fn test_iterator() {
                    (b"c", b"c"),
                    (b"fooz", b"baz"));
 
-    for &(k, v) in kvs.iter() {
-        default.put(&write_options, k, v).unwrap();
+    for &(k, v) in kvs.iter() {
+        default.put(&write_options, k, v).unwrap();
+    }
+
+    for (kv, tuple) in default.iter(&read_options).unwrap().zip(kvs.iter()) {
+        assert!(kv.key[] == tuple.val0());
+        assert!(kv.value[] == tuple.val1());
+    }
+
+    assert_eq!(0, other.iter(&read_options).unwrap().count());
+}
+
+#[test]
+fn test_create_db_options() {
+    let mut options = DatabaseOptions::new();
+    options.increase_parallelism(16);
+    options.prepare_for_bulkload();
+}
+
+#[test]
+fn test_db_options() {
+    let dir = io::TempDir::new("").unwrap();
+    let wal_dir = io::TempDir::new("").unwrap();
+    let log_dir = io::TempDir::new("").unwrap();
+
+    let mut options = DatabaseOptions::new();
+    options.set_paranoid_checks(true)
+           .set_max_open_files(Some(100))
+           .set_max_open_files(None)
+           .set_max_background_compactions(2)
+           .set_max_background_flushes(1)
+           .set_db_log_dir(log_dir.path())
+           .set_info_log_level(WarnLevel)
+           .set_forward_info_log(true)
+           .set_wal_dir(wal_dir.path())
+           .set_wal_ttl(Duration::hours(1))
+           .set_wal_size_limit(64 << 20)
+           .set_max_log_file_size(1 << 20)
+           .set_log_file_time_to_roll(Duration::days(1))
+           .set_keep_log_file_num(10)
+           .set_max_manifest_file_size(1 << 30)
+           .set_manifest_preallocation_size(1 << 20)
+           .set_table_cache_numshardbits(6)
+           .set_table_cache_remove_scan_count_limit(16)
+           .set_use_fsync(true)
+           .set_disable_data_sync(false)
+           .set_allow_os_buffer(true)
+           .set_allow_mmap_reads(true)
+           .set_allow_mmap_writes(false)
+           .set_is_fd_close_on_exec(true)
+           .set_skip_log_error_on_recovery(false)
+           .set_stats_dump_period(Duration::minutes(10))
+           .set_advise_random_on_open(true)
+           .set_access_hint_on_compaction_start(AccessHintSequential)
+           .set_use_adaptive_mutex(true)
+           .set_bytes_per_sync(1 << 20)
+           .set_delete_obsolete_files_period(Duration::hours(1))
+           .set_allow_thread_local(true);
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(dir.path(), options, cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    default.put(&WriteOptions::new(), b"key", b"val").unwrap();
+    assert_eq!(b"val", default.get(&ReadOptions::new(), b"key").unwrap().unwrap().as_slice());
+    assert!(io::fs::readdir(wal_dir.path()).unwrap().iter().any(|path| {
+        path.extension_str() == Some("log")
+    }));
+}
+
+#[test]
+fn test_info_log() {
+    assert_eq!((log::INFO, "Compaction start"),
+               info_log::parse_line("2014/10/19-12:34:56.123456 7f3a2c Compaction start\n"));
+    assert_eq!((log::WARN, "Stalling writes"),
+               info_log::parse_line("2014/10/19-12:34:56.123456 7f3a2c [WARN] Stalling writes"));
+    assert_eq!((log::ERROR, "Corruption"), info_log::parse_line("[FATAL] Corruption"));
+    assert_eq!((log::INFO, "Options.max_open_files: 5000"),
+               info_log::parse_line("Options.max_open_files: 5000"));
+
+    // The leading separator is dropped, and every other byte outside `[A-Za-z0-9._-]` replaced.
+    let log_dir = io::TempDir::new("").unwrap();
+    assert_eq!(log_dir.path().join("var_lib_db-1.d_caf__LOG"),
+               info_log::info_log_path(&Path::new("/var/lib/db-1.d/caf\u00e9"),
+                                       Some(log_dir.path())));
+    let long_path = Path::new(format!("/{}", "a".repeat(600)));
+    assert_eq!(log_dir.path().join(format!("{}_LOG", "a".repeat(495))),
+               info_log::info_log_path(&long_path, Some(log_dir.path())));
+
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = DatabaseOptions::new();
+    options.set_db_log_dir(log_dir.path()).set_forward_info_log(true);
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new())).into_iter().collect();
+    let db = Database::create(dir.path(), options, cfs).unwrap();
+    let log_file = info_log::info_log_path(dir.path(), Some(log_dir.path()));
+    assert!(io::fs::stat(&log_file).unwrap().size > 0);
+    assert!(log_file.filename_str().unwrap().ends_with("_LOG"));
+    db.flush(&FlushOptions::new()).unwrap();
+    drop(db);
+}
+
+/// Sends the module path, level and message of each record to a channel.
+struct CapturingLogger {
+    records: Sender<(String, u32, String)>
+}
+
+impl log::Logger for CapturingLogger {
+    fn log(&mut self, record: &log::LogRecord) {
+        let log::LogLevel(level) = record.level;
+        self.records.send((record.module_path.to_string(), level, format!("{}", record.args)));
+    }
+}
+
+#[test]
+fn test_info_log_forwarding() {
+    let dir = io::TempDir::new("").unwrap();
+    let log_file = dir.path().join("LOG");
+    let mut file = io::File::create(&log_file).unwrap();
+    file.write_str("2014/10/19-12:34:56.123456 7f3a2c Compaction start\n\
+                    2014/10/19-12:34:56.123457 7f3a2c [WARN] Stalling writes\n\
+                    2014/10/19-12:34:56.123458 7f3a2c [ERROR] Corruption\n\
+                    2014/10/19-12:34:56.123459 7f3a2c [FATAL] Shutting down").unwrap();
+    file.fsync().unwrap();
+
+    let (sender, receiver) = channel();
+    let logger: Box<log::Logger + Send> = box CapturingLogger { records: sender };
+    let forwarder = info_log::InfoLogForwarder::start(log_file, Some(logger));
+    // Dropping the forwarder forwards the remaining lines, including the partial last line.
+    drop(forwarder);
+    let mut records = Vec::new();
+    loop {
+        match receiver.try_recv() {
+            Ok(record) => records.push(record),
+            Err(_) => break
+        }
+    }
+
+    // Only the levels enabled for the `rocksdb` module are forwarded.
+    let expected: Vec<(String, u32, String)> =
+        [(log::INFO, "Compaction start"),
+         (log::WARN, "Stalling writes"),
+         (log::ERROR, "Corruption"),
+         (log::ERROR, "Shutting down")].iter()
+                                       .filter(|&&(level, _)| log::mod_enabled(level, "rocksdb"))
+                                       .map(|&(level, message)| {
+                                           ("rocksdb".to_string(), level, message.to_string())
+                                       })
+                                       .collect();
+    assert!(expected.len() >= 2);
+    assert_eq!(expected, records);
+}
+
+#[test]
+#[should_fail]
+fn test_db_options_negative_duration() {
+    DatabaseOptions::new().set_wal_ttl(Duration::seconds(-1));
+}
+
+#[test]
+#[should_fail]
+fn test_db_options_max_open_files_out_of_range() {
+    DatabaseOptions::new().set_max_open_files(Some(1 << 31));
+}
+
+#[test]
+#[should_fail]
+fn test_db_options_max_background_compactions_out_of_range() {
+    DatabaseOptions::new().set_max_background_compactions(1 << 31);
+}
+
+#[test]
+fn test_db_options_durations_round_up() {
+    let mut options = DatabaseOptions::new();
+    options.set_wal_ttl(Duration::milliseconds(500))
+           .set_log_file_time_to_roll(Duration::milliseconds(1500))
+           .set_stats_dump_period(Duration::zero());
+    assert_eq!(Some("1"), options.get("WAL_ttl_seconds"));
+    assert_eq!(Some("2"), options.get("log_file_time_to_roll"));
+    assert_eq!(Some("0"), options.get("stats_dump_period_sec"));
+}
+
+#[test]
+fn test_create_cf_options() {
+    let mut options = ColumnFamilyOptions::new();
+    options.optimize_level_style_compaction(2 << 26);
+    options.optimize_universal_style_compaction(2 << 26);
+}
+
+#[test]
+fn test_cf_options_lsm_shape() {
+    let dir = io::TempDir::new("").unwrap();
+
+    let mut small = ColumnFamilyOptions::new();
+    small.set_write_buffer_size(64 << 10)
+         .set_max_write_buffer_number(3)
+         .set_min_write_buffer_number_to_merge(1)
+         .set_num_levels(4)
+         .set_level0_file_num_compaction_trigger(2)
+         .set_level0_slowdown_writes_trigger(8)
+         .set_level0_stop_writes_trigger(12)
+         .set_max_mem_compaction_level(0)
+         .set_target_file_size_base(64 << 10)
+         .set_target_file_size_multiplier(2)
+         .set_max_bytes_for_level_base(256 << 10)
+         .set_max_bytes_for_level_multiplier(4)
+         .set_max_bytes_for_level_multiplier_additional(&[1, 1, 2, 2])
+         .set_expanded_compaction_factor(25)
+         .set_source_compaction_factor(1)
+         .set_max_grandparent_overlap_factor(10)
+         .set_soft_rate_limit(0.0)
+         .set_hard_rate_limit(0.0)
+         .set_rate_limit_delay_max(Duration::milliseconds(100))
+         .set_arena_block_size(0)
+         .set_purge_redundant_kvs_while_flush(true)
+         .set_verify_checksums_in_compaction(true)
+         .set_filter_deletes(false)
+         .set_max_sequential_skip_in_iterations(8);
+
+    let mut manual = ColumnFamilyOptions::new();
+    manual.set_write_buffer_size(64 << 10)
+          .set_disable_auto_compactions(true);
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("small".to_string(), small),
+                   ("manual".to_string(), manual)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let small = db.get_column_family("small").unwrap();
+    let manual = db.get_column_family("manual").unwrap();
+
+    let val = Vec::from_elem(1024, 0xABu8);
+    for i in range(0u, 2000) {
+        let key = format!("{:08}", i);
+        small.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+        manual.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+    }
+
+    assert_eq!(2000, small.iter(read_options).unwrap().count());
+    assert_eq!(2000, manual.iter(read_options).unwrap().count());
+}
+
+#[test]
+fn test_options_introspection() {
+    let mut db_options = DatabaseOptions::new();
+    db_options.increase_parallelism(4)
+              .set_max_open_files(None)
+              .set_wal_ttl(Duration::minutes(2))
+              .set_access_hint_on_compaction_start(AccessHintWillNeed);
+    assert_eq!(Some("4"), db_options.get("increase_parallelism"));
+    assert_eq!(Some("-1"), db_options.get("max_open_files"));
+    assert_eq!(Some("120"), db_options.get("WAL_ttl_seconds"));
+    assert_eq!(Some("WILLNEED"), db_options.get("access_hint_on_compaction_start"));
+    assert_eq!(None, db_options.get("use_fsync"));
+
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_write_buffer_size(1 << 20)
+              .set_max_bytes_for_level_multiplier_additional(&[1, 2, 3])
+              .set_write_buffer_size(2 << 20)
+              .set_merge_operator("add", box AddMergeOperator);
+    assert_eq!(Some("2097152"), cf_options.get("write_buffer_size"));
+    assert_eq!(Some("1:2:3"), cf_options.get("max_bytes_for_level_multiplier_additional"));
+    assert_eq!(Some("add"), cf_options.get("merge_operator"));
+    assert_eq!(3, cf_options.settings().len());
+
+    assert!(db_options.set("max_open_files", "not a number").is_err());
+    assert!(db_options.set("no_such_option", "1").is_err());
+    assert!(cf_options.set("merge_operator", "add").is_err());
+}
+
+#[test]
+fn test_options_file_round_trip() {
+    let dir = io::TempDir::new("").unwrap();
+
+    let mut db_options = DatabaseOptions::new();
+    db_options.increase_parallelism(2)
+              .set_paranoid_checks(false)
+              .set_wal_size_limit(3 << 20)
+              .set_delete_obsolete_files_period(Duration::minutes(5));
+
+    let mut default = ColumnFamilyOptions::new();
+    default.optimize_level_style_compaction(1 << 20)
+           .set_write_buffer_size(256 << 10)
+           .set_merge_operator("add", box AddMergeOperator);
+    let mut other = ColumnFamilyOptions::new();
+    other.set_num_levels(3)
+         .set_soft_rate_limit(1.5)
+         .set_max_bytes_for_level_multiplier_additional(&[1, 1, 2]);
+
+    let cf_options = vec!(("default".to_string(), default),
+                          ("other".to_string(), other)).into_iter().collect();
+
+    let mut writer = io::MemWriter::new();
+    options_file::write_options(&mut writer, &db_options, &cf_options).unwrap();
+    let text = String::from_utf8(writer.unwrap()).unwrap();
+
+    let (read_db_options, mut read_cf_options) =
+        options_file::read_options(text.as_slice()).unwrap();
+    assert_eq!(db_options.settings(), read_db_options.settings());
+    assert_eq!(vec!(("optimize_level_style_compaction".to_string(), "1048576".to_string()),
+                    ("write_buffer_size".to_string(), "262144".to_string())).as_slice(),
+               read_cf_options.find_equiv("default").unwrap().settings());
+    assert_eq!(cf_options.find_equiv("other").unwrap().settings(),
+               read_cf_options.find_equiv("other").unwrap().settings());
+
+    // Writing the options which were read reproduces the text, except for the merge operator.
+    let mut writer = io::MemWriter::new();
+    options_file::write_options(&mut writer, &read_db_options, &read_cf_options).unwrap();
+    let rewritten = String::from_utf8(writer.unwrap()).unwrap();
+    assert_eq!(text.replace("  merge_operator=add\n", ""), rewritten);
+
+    read_cf_options.find_mut(&"default".to_string())
+                   .unwrap()
+                   .set_merge_operator("add", box AddMergeOperator);
+    let db = Database::create(dir.path(), read_db_options, read_cf_options).unwrap();
+    let other = db.get_column_family("other").unwrap();
+    other.put(&WriteOptions::new(), b"key", b"val").unwrap();
+    assert_eq!(b"val", other.get(&ReadOptions::new(), b"key").unwrap().unwrap().as_slice());
+}
+
+#[test]
+fn test_options_file_errors() {
+    assert!(options_file::read_options("write_buffer_size=1").is_err());
+    assert!(options_file::read_options("[CFOptions default]").is_err());
+    assert!(options_file::read_options("[CFOptions \"a\"]\n[CFOptions \"a\"]").is_err());
+    assert!(options_file::read_options("[DBOptions]\n  write_buffer_size=1").is_err());
+    assert!(options_file::read_options("[Unknown]").is_err());
+
+    assert!(options_file::read_options("[CFOptions \"a\\q\"]").is_err());
+    assert!(options_file::read_options("[CFOptions \"a\"b\"]").is_err());
+
+    // Values outside the range of a setter are errors, not panics.
+    for line in ["max_background_compactions=-1",
+                 "max_background_flushes=2147483648",
+                 "table_cache_numshardbits=-2",
+                 "WAL_ttl_seconds=-1",
+                 "WAL_ttl_seconds=9223372036854775807",
+                 "WAL_size_limit_MB=18446744073709551615",
+                 "log_file_time_to_roll=-5",
+                 "stats_dump_period_sec=4294967296",
+                 "delete_obsolete_files_period_micros=-1"].iter() {
+        assert!(options_file::read_options(format!("[DBOptions]\n{}", line).as_slice()).is_err());
+    }
+    assert!(options_file::read_options(
+        "[CFOptions \"a\"]\n  rate_limit_delay_max_milliseconds=-1").is_err());
+    assert!(options_file::read_options(
+        "[CFOptions \"a\"]\n  rate_limit_delay_max_milliseconds=4294967296").is_err());
+
+    let (_, cf_options) = options_file::read_options(
+        "# comment\n[DBOptions]\n\n[CFOptions \"a\"]\n  num_levels = 4\n").unwrap();
+    assert_eq!(Some("4"), cf_options.find_equiv("a").unwrap().get("num_levels"));
+
+    // Column family names with quotes, backslashes and line breaks are escaped.
+    let name = "a \"b\" \\c\nd\re";
+    let mut options = ColumnFamilyOptions::new();
+    options.set_num_levels(5);
+    let cf_options = vec!((name.to_string(), options)).into_iter().collect();
+    let mut writer = io::MemWriter::new();
+    options_file::write_options(&mut writer, &DatabaseOptions::new(), &cf_options).unwrap();
+    let text = String::from_utf8(writer.unwrap()).unwrap();
+    let (_, read_cf_options) = options_file::read_options(text.as_slice()).unwrap();
+    assert_eq!(Some("5"), read_cf_options.find_equiv(name).unwrap().get("num_levels"));
+}
+
+#[test]
+fn test_options_presets() {
+    let dir = io::TempDir::new("").unwrap();
+
+    let mut db_options = DatabaseOptions::new();
+    db_options.preset_write_heavy(4);
+    assert_eq!(vec!(("increase_parallelism".to_string(), "4".to_string()),
+                    ("bytes_per_sync".to_string(), "1048576".to_string())).as_slice(),
+               db_options.settings());
+
+    let mut point_lookup_db = DatabaseOptions::new();
+    point_lookup_db.preset_point_lookup();
+    assert_eq!(Some("-1"), point_lookup_db.get("max_open_files"));
+    assert_eq!(Some("true"), point_lookup_db.get("advise_random_on_open"));
+    let mut range_scan_db = DatabaseOptions::new();
+    range_scan_db.preset_range_scan();
+    assert_eq!(Some("false"), range_scan_db.get("advise_random_on_open"));
+    assert_eq!(Some("SEQUENTIAL"), range_scan_db.get("access_hint_on_compaction_start"));
+    let mut low_memory_db = DatabaseOptions::new();
+    low_memory_db.preset_low_memory();
+    assert_eq!(Some("64"), low_memory_db.get("max_open_files"));
+    assert_eq!(Some("1"), low_memory_db.get("max_background_compactions"));
+    assert_eq!(Some("1"), low_memory_db.get("max_background_flushes"));
+    assert_eq!(Some("2"), low_memory_db.get("keep_log_file_num"));
+
+    let mut point_lookup = ColumnFamilyOptions::new();
+    point_lookup.preset_point_lookup(8 << 20);
+    assert_eq!(Some("536870912"), point_lookup.get("optimize_level_style_compaction"));
+    assert_eq!(Some("8388608"), point_lookup.get("optimize_for_point_lookup"));
+    let mut write_heavy = ColumnFamilyOptions::new();
+    write_heavy.preset_write_heavy(8 << 20);
+    assert_eq!(Some("8388608"), write_heavy.get("optimize_universal_style_compaction"));
+    assert_eq!(Some("40"), write_heavy.get("level0_slowdown_writes_trigger"));
+    assert_eq!(Some("56"), write_heavy.get("level0_stop_writes_trigger"));
+    assert_eq!(Some("{bloom_filter_bits_per_key=10}"),
+               write_heavy.get("block_based_table_factory"));
+    let mut range_scan = ColumnFamilyOptions::new();
+    range_scan.preset_range_scan(8 << 20);
+    assert_eq!(Some("67108864"), range_scan.get("target_file_size_base"));
+    assert_eq!(Some("{block_size=65536;block_cache_size=8388608}"),
+               range_scan.get("block_based_table_factory"));
+    let mut low_memory = ColumnFamilyOptions::new();
+    low_memory.preset_low_memory();
+    assert_eq!(Some("1048576"), low_memory.get("write_buffer_size"));
+    assert_eq!(Some("2"), low_memory.get("max_write_buffer_number"));
+    assert_eq!(Some("{bloom_filter_bits_per_key=10;block_cache_size=1048576}"),
+               low_memory.get("block_based_table_factory"));
+
+    let names = ["point_lookup", "write_heavy", "range_scan", "low_memory"];
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("point_lookup".to_string(), point_lookup),
+                   ("write_heavy".to_string(), write_heavy),
+                   ("range_scan".to_string(), range_scan),
+                   ("low_memory".to_string(), low_memory)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), db_options, cfs).unwrap();
+    for name in names.iter() {
+        let cf = db.get_column_family(*name).unwrap();
+        for i in range(0u, 1000) {
+            let key = format!("{:08}", i);
+            cf.put(write_options, key.as_bytes(), key.as_bytes()).unwrap();
+        }
+        for i in range(0u, 1000) {
+            let key = format!("{:08}", i);
+            assert_eq!(key.as_bytes(),
+                       cf.get(read_options, key.as_bytes()).unwrap().unwrap().as_slice());
+        }
+        assert!(cf.get(read_options, b"missing").unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_block_based_table_options() {
+    let mut table_options = BlockBasedTableOptions::new();
+    table_options.set_block_size(16 << 10)
+                 .set_bloom_filter_bits_per_key(10)
+                 .set_no_block_cache()
+                 .set_whole_key_filtering(false);
+    let text = table_options.to_string();
+    assert_eq!("{block_size=16384;bloom_filter_bits_per_key=10;no_block_cache=true;\
+                whole_key_filtering=false}", text.as_slice());
+    assert_eq!(Some(table_options.clone()), from_str(text.as_slice()));
+    assert_eq!(Some(BlockBasedTableOptions::new()), from_str("{}"));
+    assert_eq!(None::<BlockBasedTableOptions>, from_str("{block_size=big}"));
+    assert_eq!(None::<BlockBasedTableOptions>, from_str("{no_such_option=1}"));
+
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_block_based_table_options(&table_options);
+    let mut replayed = ColumnFamilyOptions::new();
+    replayed.set("block_based_table_factory", text.as_slice()).unwrap();
+    assert_eq!(cf_options.settings(), replayed.settings());
+}
+
+#[test]
+fn test_universal_compaction() {
+    let dir = io::TempDir::new("").unwrap();
+
+    let mut universal_options = UniversalCompactionOptions::new();
+    universal_options.set_size_ratio(10)
+                     .set_min_merge_width(2)
+                     .set_max_merge_width(4)
+                     .set_max_size_amplification_percent(50)
+                     .set_compression_size_percent(-1)
+                     .set_stop_style(StopStyleSimilarSize);
+    let text = universal_options.to_string();
+    assert_eq!(Some(universal_options.clone()), from_str(text.as_slice()));
+    assert_eq!(None::<UniversalCompactionOptions>, from_str("{stop_style=kNoSuchStyle}"));
+
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_write_buffer_size(64 << 10)
+              .set_compaction_style(CompactionStyleUniversal)
+              .set_universal_compaction_options(&universal_options);
+    assert_eq!(Some("kCompactionStyleUniversal"), cf_options.get("compaction_style"));
+    assert_eq!(Some(text.as_slice()), cf_options.get("compaction_options_universal"));
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("universal".to_string(), cf_options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let universal = db.get_column_family("universal").unwrap();
+
+    for round in range(0u8, 4) {
+        let val = Vec::from_elem(1024, round);
+        for i in range(0u, 500) {
+            let key = format!("{:08}", i);
+            universal.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+        }
+    }
+    let val = Vec::from_elem(1024, 3u8);
+    for i in range(0u, 500) {
+        let key = format!("{:08}", i);
+        assert_eq!(val.as_slice(),
+                   universal.get(read_options, key.as_bytes()).unwrap().unwrap().as_slice());
+    }
+    assert_eq!(500, universal.iter(read_options).unwrap().count());
+}
+
+#[test]
+fn test_fifo_compaction() {
+    let dir = io::TempDir::new("").unwrap();
+
+    let mut fifo_options = FifoCompactionOptions::new();
+    fifo_options.set_max_table_files_size(256 << 10);
+    assert_eq!(Some(fifo_options.clone()), from_str(fifo_options.to_string().as_slice()));
+
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_write_buffer_size(64 << 10)
+              .set_compaction_style(CompactionStyleFifo)
+              .set_fifo_compaction_options(&fifo_options);
+    assert_eq!(Some("kCompactionStyleFIFO"), cf_options.get("compaction_style"));
+
+    // FIFO-style compaction requires all table files to be kept open.
+    let mut unbounded_files = ColumnFamilyOptions::new();
+    unbounded_files.set_compaction_style(CompactionStyleFifo);
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("events".to_string(), unbounded_files)).into_iter().collect();
+    assert!(Database::create(dir.path(), DatabaseOptions::new(), cfs).is_err());
+
+    let mut db_options = DatabaseOptions::new();
+    db_options.set_max_open_files(None);
+
+    let cfs = vec!(("default".to_string(), ColumnFamilyOptions::new()),
+                   ("events".to_string(), cf_options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), db_options, cfs).unwrap();
+    let events = db.get_column_family("events").unwrap();
+
+    // Write 4MB of events, far more than the FIFO bound allows to be retained.
+    let val = Vec::from_elem(1024, 0xABu8);
+    for i in range(0u, 4096) {
+        let key = format!("{:08}", i);
+        events.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+    }
+
+    // Files are deleted by compactions, so wait for one to run over the flushed files.
+    events.flush(&FlushOptions::new()).unwrap();
+    events.compact_range(None, None);
+
+    // The most recent event is still readable, but the oldest events have aged out.
+    assert!(events.get(read_options, b"00004095").unwrap().is_some());
+    assert!(events.iter(read_options).unwrap().count() < 4096);
+}
+
+#[test]
+fn test_memtable_reps() {
+    let dir = io::TempDir::new("").unwrap();
+
+    let reps = vec!(("skip_list", SkipListRep),
+                    ("vector", VectorRep),
+                    ("hash_skip_list", HashSkipListRep(1024, 4, 4)),
+                    ("hash_link_list", HashLinkListRep(1024)));
+
+    let mut cfs = HashMap::new();
+    cfs.insert("default".to_string(), ColumnFamilyOptions::new());
+    for &(name, ref rep) in reps.iter() {
+        let mut cf_options = ColumnFamilyOptions::new();
+        cf_options.set_prefix_extractor("fixed4", box FixedPrefixTransform::new(4))
+                  .set_memtable_rep(rep.clone())
+                  .set_memtable_prefix_bloom_bits(1 << 16)
+                  .set_memtable_prefix_bloom_probes(4)
+                  .set_bloom_locality(1);
+        if *rep == SkipListRep {
+            cf_options.set_inplace_update_support(true)
+                      .set_inplace_update_num_locks(64);
+        }
+        assert_eq!(Some(rep.clone()), from_str(cf_options.get("memtable_factory").unwrap()));
+        cfs.insert(name.to_string(), cf_options);
+    }
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    for &(name, _) in reps.iter() {
+        let cf = db.get_column_family(name).unwrap();
+        for i in range(0u, 100) {
+            let key = format!("{:04}{:04}", i % 10, i);
+            cf.put(write_options, key.as_bytes(), b"old").unwrap();
+            cf.put(write_options, key.as_bytes(), b"new").unwrap();
+        }
+        for i in range(0u, 100) {
+            let key = format!("{:04}{:04}", i % 10, i);
+            assert_eq!(b"new", cf.get(read_options, key.as_bytes()).unwrap().unwrap().as_slice());
+        }
+        assert!(cf.get(read_options, b"00110011").unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_hash_memtable_requires_prefix_extractor() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_memtable_rep(HashLinkListRep(1024));
+    let cfs = vec!(("default".to_string(), cf_options)).into_iter().collect();
+    assert!(Database::create(dir.path(), DatabaseOptions::new(), cfs).is_err());
+}
+
+#[test]
+fn test_skip_list_memtable_after_replacement() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_memtable_rep(VectorRep).set_memtable_rep(SkipListRep);
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    assert!(Database::create(dir.path(), DatabaseOptions::new(), cfs).is_err());
+
+    let mut options = ColumnFamilyOptions::new();
+    options.optimize_for_point_lookup(1 << 20).set_memtable_rep(SkipListRep);
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    assert!(Database::create(dir.path(), DatabaseOptions::new(), cfs).is_err());
+
+    let mut options = ColumnFamilyOptions::new();
+    options.set_memtable_rep(SkipListRep).set_memtable_rep(VectorRep);
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    assert!(Database::create(dir.path(), DatabaseOptions::new(), cfs).is_ok());
+}
+
+#[test]
+fn test_plain_table() {
+    let dir = io::TempDir::new("").unwrap();
+
+    let mut table_options = PlainTableOptions::new();
+    table_options.set_user_key_len(8)
+                 .set_bloom_bits_per_key(10)
+                 .set_hash_table_ratio(0.5)
+                 .set_index_sparseness(8);
+    assert_eq!(Some(table_options.clone()), from_str(table_options.to_string().as_slice()));
+
+    let mut db_options = DatabaseOptions::new();
+    db_options.set_allow_mmap_reads(true);
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_write_buffer_size(64 << 10)
+              .set_prefix_extractor("fixed4", box FixedPrefixTransform::new(4))
+              .set_plain_table_options(&table_options);
+
+    let cfs = vec!(("default".to_string(), cf_options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), db_options, cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    // Write enough data to flush several plain table files.
+    let val = Vec::from_elem(1024, 0xABu8);
+    for i in range(0u, 1000) {
+        let key = format!("{:04}{:04}", i % 10, i);
+        default.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
     }
-
-    for (kv, tuple) in default.iter(&read_options).unwrap().zip(kvs.iter()) {
-        assert!(kv.key[] == tuple.val0());
-        assert!(kv.value[] == tuple.val1());
+    for i in range(0u, 1000) {
+        let key = format!("{:04}{:04}", i % 10, i);
+        assert_eq!(val.as_slice(),
+                   default.get(read_options, key.as_bytes()).unwrap().unwrap().as_slice());
     }
-
-    assert_eq!(0, other.iter(&read_options).unwrap().count());
+    assert!(default.get(read_options, b"00110011").unwrap().is_none());
 }
 
 #[test]
-fn test_create_db_options() {
-    let mut options = DatabaseOptions::new();
-    options.increase_parallelism(16);
-    options.prepare_for_bulkload();
-}
+fn test_plain_table_prerequisites() {
+    let dir = io::TempDir::new("").unwrap();
 
-#[test]
-fn test_create_cf_options() {
-    let mut options = ColumnFamilyOptions::new();
-    options.optimize_level_style_compaction(2 << 26);
-    options.optimize_universal_style_compaction(2 << 26);
+    let mut mmap_options = DatabaseOptions::new();
+    mmap_options.set_allow_mmap_reads(true);
+
+    // Missing prefix extractor.
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_plain_table_options(&PlainTableOptions::new());
+    let cfs = vec!(("default".to_string(), cf_options)).into_iter().collect();
+    assert!(Database::create(dir.path(), mmap_options, cfs).is_err());
+
+    // Missing memory mapped reads.
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_prefix_extractor("fixed4", box FixedPrefixTransform::new(4))
+              .set_plain_table_options(&PlainTableOptions::new());
+    let cfs = vec!(("default".to_string(), cf_options)).into_iter().collect();
+    assert!(Database::create(dir.path(), DatabaseOptions::new(), cfs).is_err());
+
+    // A block-based table replacing the plain table has no prerequisites.
+    let mut cf_options = ColumnFamilyOptions::new();
+    cf_options.set_plain_table_options(&PlainTableOptions::new())
+              .set_block_based_table_options(&BlockBasedTableOptions::new());
+    let cfs = vec!(("default".to_string(), cf_options)).into_iter().collect();
+    assert!(Database::create(dir.path(), DatabaseOptions::new(), cfs).is_ok());
 }
 
 #[test]
@@ -226,6 +1393,303 @@ fn test_associative_merge() {
                default.get(read_options, b"key").unwrap().unwrap().as_slice());
 }
 
+#[test]
+fn test_set_merge() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_merge_operator("set", box SetMergeOperator);
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    default.merge(write_options, b"key", SetMergeOperator::add(b"c").unwrap().as_slice()).unwrap();
+    default.merge(write_options, b"key", SetMergeOperator::add(b"a").unwrap().as_slice()).unwrap();
+    default.merge(write_options, b"key", SetMergeOperator::add(b"b").unwrap().as_slice()).unwrap();
+    default.merge(write_options, b"key", SetMergeOperator::add(b"a").unwrap().as_slice()).unwrap();
+    default.merge(write_options, b"key", SetMergeOperator::remove(b"b").unwrap().as_slice()).unwrap();
+    default.merge(write_options, b"key", SetMergeOperator::remove(b"z").unwrap().as_slice()).unwrap();
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    assert_eq!(vec!(b"a".to_vec(), b"c".to_vec()),
+               SetMergeOperator::read_set(val.as_slice()).unwrap());
+}
+
+#[test]
+fn test_bounded_list_merge() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_merge_operator("bounded-list", box BoundedListMergeOperator::new(3));
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    let operand = BoundedListMergeOperator::append(&[b"1", b"2"]).unwrap();
+    default.put(write_options, b"key", operand.as_slice()).unwrap();
+    for element in [b"3", b"4", b"5"].iter() {
+        let operand = BoundedListMergeOperator::append(&[*element]).unwrap();
+        default.merge(write_options, b"key", operand.as_slice()).unwrap();
+    }
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    assert_eq!(vec!(b"3".to_vec(), b"4".to_vec(), b"5".to_vec()),
+               BoundedListMergeOperator::read_list(val.as_slice()).unwrap());
+}
+
+#[test]
+fn test_unique_list_merge() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_merge_operator("unique-list", box UniqueListMergeOperator);
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    for elements in [&[b"b", b"a"], &[b"a", b"c"], &[b"b", b"d"]].iter() {
+        let operand = UniqueListMergeOperator::append(*elements).unwrap();
+        default.merge(write_options, b"key", operand.as_slice()).unwrap();
+    }
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    assert_eq!(vec!(b"b".to_vec(), b"a".to_vec(), b"c".to_vec(), b"d".to_vec()),
+               UniqueListMergeOperator::read_list(val.as_slice()).unwrap());
+}
+
+#[test]
+fn test_hyperloglog_merge() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_merge_operator("hll", box HyperLogLogMergeOperator::new(12));
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    // Items 0 through 9999 are recorded individually, twice each, and items 5000 through 14999
+    // through a pre-built sketch.
+    for i in range(0u, 10000) {
+        let item = i.to_string();
+        let operand = HyperLogLogMergeOperator::item(item.as_bytes());
+        default.merge(write_options, b"key", operand.as_slice()).unwrap();
+        default.merge(write_options, b"key", operand.as_slice()).unwrap();
+    }
+    let mut sketch = HyperLogLog::new(12);
+    for i in range(5000u, 15000) {
+        sketch.insert(i.to_string().as_bytes());
+    }
+    let operand = HyperLogLogMergeOperator::sketch(&sketch);
+    default.merge(write_options, b"key", operand.as_slice()).unwrap();
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    let estimate = HyperLogLogMergeOperator::estimate(val.as_slice()).unwrap();
+    assert!(estimate > 14000.0 && estimate < 16000.0, "estimate: {}", estimate);
+}
+
+#[test]
+fn test_count_min_merge() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_merge_operator("count-min", box CountMinMergeOperator::new(1024, 4));
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    for _ in range(0u, 10) {
+        let operand = CountMinMergeOperator::item(b"a", 1).unwrap();
+        default.merge(write_options, b"key", operand.as_slice()).unwrap();
+    }
+    let operand = CountMinMergeOperator::item(b"b", 7).unwrap();
+    default.merge(write_options, b"key", operand.as_slice()).unwrap();
+
+    let mut sketch = CountMinSketch::new(1024, 4);
+    sketch.insert(b"a", 5);
+    let operand = CountMinMergeOperator::sketch(&sketch).unwrap();
+    default.merge(write_options, b"key", operand.as_slice()).unwrap();
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    assert_eq!(15, CountMinMergeOperator::estimate(val.as_slice(), b"a").unwrap());
+    assert_eq!(7, CountMinMergeOperator::estimate(val.as_slice(), b"b").unwrap());
+    assert_eq!(0, CountMinMergeOperator::estimate(val.as_slice(), b"c").unwrap());
+}
+
+#[test]
+fn test_sketch_encoding() {
+    // Sketches are persisted, so the registers and counters an item maps to must never change.
+    let mut hll = HyperLogLog::new(4);
+    for item in ["a", "b", "c"].iter() {
+        hll.insert(item.as_bytes());
+    }
+    assert_eq!(vec!(4u8, 0, 3, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 1, 0), hll.encode());
+
+    let mut cms = CountMinSketch::new(8, 2);
+    cms.insert(b"a", 1);
+    cms.insert(b"b", u64::MAX);
+    cms.insert(b"b", 1);
+    let encoded = cms.encode().unwrap();
+    let counter = |i: uint| io::BufReader::new(encoded.slice(8 + 8 * i, 16 + 8 * i)).read_be_u64();
+    assert_eq!(vec!(0, 1, 0, 0, u64::MAX, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, u64::MAX),
+               range(0u, 16).map(|i| counter(i).unwrap()).collect::<Vec<u64>>());
+
+    assert!(CountMinSketch::decode(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+}
+
+#[test]
+fn test_sketch_partial_merge_keeps_parameters() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_merge_operator("count-min", box CountMinMergeOperator::new(1024, 4))
+           .set_min_partial_merge_operands(2);
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let write_options = &WriteOptions::new();
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    // The stored sketch has different dimensions from those of the merge operator.
+    default.put(write_options, b"key", CountMinSketch::new(8, 2).encode().unwrap().as_slice())
+           .unwrap();
+    default.flush(&FlushOptions::new()).unwrap();
+    for _ in range(0u, 10) {
+        let operand = CountMinMergeOperator::item(b"a", 1).unwrap();
+        default.merge(write_options, b"key", operand.as_slice()).unwrap();
+    }
+    default.flush(&FlushOptions::new()).unwrap();
+    default.compact_range(None, None);
+
+    let val = default.get(&ReadOptions::new(), b"key").unwrap().unwrap();
+    let cms = CountMinSketch::decode(val.as_slice()).unwrap();
+    assert_eq!((8, 2), (cms.width(), cms.depth()));
+    assert_eq!(10, cms.estimate(b"a"));
+}
+
+#[test]
+fn test_json_merge() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_merge_operator("json", box JsonMergeOperator).set_min_partial_merge_operands(2);
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    default.put(write_options, b"key",
+                br#"{"name": "a", "tags": ["x"], "address": {"city": "b", "zip": "1"}}"#).unwrap();
+    default.merge(write_options, b"key", br#"{"address": {"zip": null, "street": "c"}}"#).unwrap();
+    default.merge(write_options, b"key", br#"{"name": null, "tags": ["y", "z"]}"#).unwrap();
+    default.merge(write_options, b"key", br#"{"age": 3, "address": {"street": "d"}}"#).unwrap();
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    let expected = json::from_str(
+        r#"{"tags": ["y", "z"], "address": {"city": "b", "street": "d"}, "age": 3}"#).unwrap();
+    assert_eq!(expected, JsonMergeOperator::read_document(val.as_slice()).unwrap());
+
+    // Replacing a member with a scalar and then patching it as an object can not be composed into
+    // a single patch. The key has no base value, so flushing partially merges its operands into a
+    // patch sequence, which the compaction then merges with the later operands.
+    default.merge(write_options, b"other", br#"{"a": {"b": 1, "c": 2}}"#).unwrap();
+    default.merge(write_options, b"other", br#"{"a": 1}"#).unwrap();
+    default.merge(write_options, b"other", br#"{"a": {"c": null, "d": 4}}"#).unwrap();
+    default.flush(&FlushOptions::new()).unwrap();
+    default.merge(write_options, b"other", br#"{"a": {"e": 5}}"#).unwrap();
+    default.merge(write_options, b"other", br#"{"b": [1]}"#).unwrap();
+
+    let expected = json::from_str(r#"{"a": {"d": 4, "e": 5}, "b": [1]}"#).unwrap();
+    let val = default.get(read_options, b"other").unwrap().unwrap();
+    assert_eq!(expected, JsonMergeOperator::read_document(val.as_slice()).unwrap());
+
+    default.flush(&FlushOptions::new()).unwrap();
+    default.compact_range(None, None);
+    let val = default.get(read_options, b"other").unwrap().unwrap();
+    assert_eq!(expected, JsonMergeOperator::read_document(val.as_slice()).unwrap());
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    let expected = json::from_str(
+        r#"{"tags": ["y", "z"], "address": {"city": "b", "street": "d"}, "age": 3}"#).unwrap();
+    assert_eq!(expected, JsonMergeOperator::read_document(val.as_slice()).unwrap());
+}
+
+/// Adds signed deltas to an unsigned counter, saturating at zero.
+struct CounterMergeOperator;
+
+impl TypedMergeOperator<u64, i64> for CounterMergeOperator {
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<u64>,
+                  operands: Vec<i64>)
+                  -> io::IoResult<u64> {
+        let total = operands.iter().fold(existing_val.unwrap_or(0) as i64,
+                                         |acc, &delta| acc + delta);
+        Ok(if total < 0 { 0 } else { total as u64 })
+    }
+
+    fn partial_merge(&self, _key: &[u8], operands: Vec<i64>) -> io::IoResult<i64> {
+        Ok(operands.iter().fold(0, |acc, &delta| acc + delta))
+    }
+}
+
+#[test]
+fn test_typed_merge() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_typed_merge_operator("counter", CounterMergeOperator);
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    default.put(write_options, b"key", 10u64.encode().unwrap().as_slice()).unwrap();
+    for &delta in [5i64, -3, 7, -1].iter() {
+        default.merge(write_options, b"key", delta.encode().unwrap().as_slice()).unwrap();
+    }
+    default.merge(write_options, b"empty", (-4i64).encode().unwrap().as_slice()).unwrap();
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    assert_eq!(18u64, Codec::decode(val.as_slice()).unwrap());
+    let val = default.get(read_options, b"empty").unwrap().unwrap();
+    assert_eq!(0u64, Codec::decode(val.as_slice()).unwrap());
+}
+
+#[test]
+#[should_fail]
+fn test_typed_merge_decode_fail() {
+    let dir = io::TempDir::new("").unwrap();
+    let mut options = ColumnFamilyOptions::new();
+    options.set_typed_merge_operator("counter", CounterMergeOperator);
+
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    default.merge(write_options, b"key", b"not an integer").unwrap();
+    default.get(read_options, b"key").unwrap();
+}
+
 #[test]
 #[should_fail]
 fn test_merge_fail() {
@@ -287,3 +1751,236 @@ fn test_write_batch() {
     assert_eq!(default.get(&read_options, b"key").unwrap().unwrap().as_slice(), b"val2");
     assert!(other.get(&read_options, b"key").unwrap().is_none());
 }
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+//// Merge Chains
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Sums `u64` operands while recording the largest number of operands passed to `full_merge` and
+/// the smallest number of operands passed to `partial_merge`.
+struct TrackingMergeOperator {
+    max_full_merge_operands: Arc<AtomicUint>,
+    min_partial_merge_operands: Arc<AtomicUint>
+}
+
+impl TrackingMergeOperator {
+    fn new() -> TrackingMergeOperator {
+        TrackingMergeOperator { max_full_merge_operands: Arc::new(AtomicUint::new(0)),
+                                min_partial_merge_operands: Arc::new(AtomicUint::new(uint::MAX)) }
+    }
+
+    fn sum(operands: Operands) -> io::IoResult<u64> {
+        let mut sum = 0;
+        for operand in operands {
+            sum += try!(AddMergeOperator::read_u64(operand));
+        }
+        Ok(sum)
+    }
+}
+
+/// Store `count` in `counter` if `replace(count, current)` holds.
+fn record(counter: &AtomicUint, count: uint, replace: |uint, uint| -> bool) {
+    loop {
+        let current = counter.load(SeqCst);
+        if !replace(count, current) || counter.compare_and_swap(current, count, SeqCst) == current {
+            break;
+        }
+    }
+}
+
+impl Clone for TrackingMergeOperator {
+    fn clone(&self) -> TrackingMergeOperator {
+        TrackingMergeOperator {
+            max_full_merge_operands: self.max_full_merge_operands.clone(),
+            min_partial_merge_operands: self.min_partial_merge_operands.clone()
+        }
+    }
+}
+
+impl MergeOperator for TrackingMergeOperator {
+    fn full_merge(&self,
+                  _key: &[u8],
+                  existing_val: Option<&[u8]>,
+                  operands: Operands)
+                  -> io::IoResult<Vec<u8>> {
+        record(&*self.max_full_merge_operands, operands.indexable(), |count, max| count > max);
+        let existing = match existing_val {
+            Some(val) => try!(AddMergeOperator::read_u64(val)),
+            None => 0
+        };
+        AddMergeOperator::write_u64(existing + try!(TrackingMergeOperator::sum(operands)))
+    }
+
+    fn partial_merge(&self,
+                     _key: &[u8],
+                     operands: Operands)
+                     -> io::IoResult<Vec<u8>> {
+        record(&*self.min_partial_merge_operands, operands.indexable(), |count, min| count < min);
+        AddMergeOperator::write_u64(try!(TrackingMergeOperator::sum(operands)))
+    }
+}
+
+/// Column family options with small memtables, so that long merge chains span many flushes and
+/// compactions.
+fn merge_chain_cfs(merge_operator: TrackingMergeOperator) -> HashMap<String, ColumnFamilyOptions> {
+    let mut options = ColumnFamilyOptions::new();
+    options.optimize_level_style_compaction(1 << 16);
+    options.set_merge_operator("tracking", box merge_operator);
+    vec!(("default".to_string(), options)).into_iter().collect()
+}
+
+#[test]
+fn test_merge_chain() {
+    let dir = io::TempDir::new("").unwrap();
+    let merge_operator = TrackingMergeOperator::new();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    {
+        let cfs = merge_chain_cfs(merge_operator.clone());
+        let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+        let default = db.get_column_family("default").unwrap();
+
+        // Part of the chain is flushed to a table file, and the rest is merged with it by a
+        // compaction.
+        for i in range(1u64, 5001) {
+            default.merge(write_options, b"key", AddMergeOperator::write_u64(i).unwrap().as_slice())
+                   .unwrap();
+            if i == 2500 {
+                default.flush(&FlushOptions::new()).unwrap();
+            }
+        }
+        let val = default.get(read_options, b"key").unwrap().unwrap();
+        assert_eq!(5000 * 5001 / 2, AddMergeOperator::read_u64(val.as_slice()).unwrap());
+
+        default.flush(&FlushOptions::new()).unwrap();
+        default.compact_range(None, None);
+        let val = default.get(read_options, b"key").unwrap().unwrap();
+        assert_eq!(5000 * 5001 / 2, AddMergeOperator::read_u64(val.as_slice()).unwrap());
+    }
+
+    // Reopening replays the write ahead log and flushes the recovered memtable.
+    let cfs = merge_chain_cfs(merge_operator.clone());
+    let db = Database::open(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+    default.merge(write_options, b"key", AddMergeOperator::write_u64(1).unwrap().as_slice())
+           .unwrap();
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    assert_eq!(5000 * 5001 / 2 + 1, AddMergeOperator::read_u64(val.as_slice()).unwrap());
+}
+
+#[test]
+fn test_merge_chain_with_puts_and_deletes() {
+    let dir = io::TempDir::new("").unwrap();
+    let merge_operator = TrackingMergeOperator::new();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+    let mut expected: HashMap<String, Option<u64>> = HashMap::new();
+
+    {
+        let cfs = merge_chain_cfs(merge_operator.clone());
+        let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+        let default = db.get_column_family("default").unwrap();
+
+        for i in range(0u64, 20000) {
+            let key = format!("key-{}", i % 37);
+            let val = AddMergeOperator::write_u64(i).unwrap();
+            let current = expected.find(&key).and_then(|val| *val);
+            let next = match i % 101 {
+                0 => {
+                    default.delete(write_options, key.as_bytes()).unwrap();
+                    None
+                },
+                1...5 => {
+                    default.put(write_options, key.as_bytes(), val.as_slice()).unwrap();
+                    Some(i)
+                },
+                _ => {
+                    default.merge(write_options, key.as_bytes(), val.as_slice()).unwrap();
+                    Some(current.unwrap_or(0) + i)
+                }
+            };
+            expected.insert(key, next);
+            if i % 5000 == 4999 {
+                default.flush(&FlushOptions::new()).unwrap();
+            }
+        }
+        default.compact_range(None, None);
+    }
+
+    let cfs = merge_chain_cfs(merge_operator.clone());
+    let db = Database::open(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    for (key, val) in expected.iter() {
+        let actual = default.get(read_options, key.as_bytes())
+                            .unwrap()
+                            .map(|val| AddMergeOperator::read_u64(val.as_slice()).unwrap());
+        assert_eq!(*val, actual);
+    }
+
+    let live = expected.values().filter(|val| val.is_some()).count();
+    assert_eq!(live, default.iter(read_options).unwrap().count());
+}
+
+#[test]
+fn test_max_successive_merges() {
+    let dir = io::TempDir::new("").unwrap();
+    let merge_operator = TrackingMergeOperator::new();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let mut options = ColumnFamilyOptions::new();
+    options.set_max_successive_merges(10);
+    options.set_merge_operator("tracking", box merge_operator.clone());
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    for i in range(1u64, 1001) {
+        default.merge(write_options, b"key", AddMergeOperator::write_u64(i).unwrap().as_slice())
+               .unwrap();
+    }
+
+    let val = default.get(read_options, b"key").unwrap().unwrap();
+    assert_eq!(1000 * 1001 / 2, AddMergeOperator::read_u64(val.as_slice()).unwrap());
+    assert!(merge_operator.max_full_merge_operands.load(SeqCst) <= 10);
+}
+
+#[test]
+fn test_min_partial_merge_operands() {
+    let dir = io::TempDir::new("").unwrap();
+    let merge_operator = TrackingMergeOperator::new();
+    let read_options = &ReadOptions::new();
+    let write_options = &WriteOptions::new();
+
+    let mut options = ColumnFamilyOptions::new();
+    options.optimize_level_style_compaction(1 << 16);
+    options.set_min_partial_merge_operands(8);
+    options.set_merge_operator("tracking", box merge_operator.clone());
+    let cfs = vec!(("default".to_string(), options)).into_iter().collect();
+
+    let db = Database::create(dir.path(), DatabaseOptions::new(), cfs).unwrap();
+    let default = db.get_column_family("default").unwrap();
+
+    // Flushing merges the operands of each key in the memtable, which has no base value to fully
+    // merge them with.
+    for i in range(0u64, 10000) {
+        let key = format!("key-{}", i % 13);
+        let val = AddMergeOperator::write_u64(1).unwrap();
+        default.merge(write_options, key.as_bytes(), val.as_slice()).unwrap();
+    }
+    default.flush(&FlushOptions::new()).unwrap();
+    default.compact_range(None, None);
+
+    for i in range(0u, 13) {
+        let key = format!("key-{}", i);
+        let val = default.get(read_options, key.as_bytes()).unwrap().unwrap();
+        let expected = if i < 10000 % 13 { 10000 / 13 + 1 } else { 10000 / 13 };
+        assert_eq!(expected, AddMergeOperator::read_u64(val.as_slice()).unwrap());
+    }
+    let min_partial_merge_operands = merge_operator.min_partial_merge_operands.load(SeqCst);
+    assert!(min_partial_merge_operands != uint::MAX, "partial_merge was not called");
+    assert!(min_partial_merge_operands >= 8);
+}